@@ -0,0 +1,110 @@
+//! Channels that move the ownership of `ThreadCells` between threads.
+//!
+//! A cell sent through the channel is owned by the sending thread up to `send()` and by the
+//! receiving thread from `recv()` on. In between it is moved through the channel and nothing
+//! else can observe it.
+
+use core::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::{
+    diagnostics, CurrentThread, OwnerIdentity, OwnerWord, ThreadCell, FLAGS, FROZEN, STICKY,
+};
+
+pub use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+
+/// Creates a channel transferring the ownership of `ThreadCells`.
+#[must_use]
+pub fn channel<T: Send>() -> (Sender<T>, Receiver<T>) {
+    let (sender, receiver) = mpsc::channel();
+    (Sender(sender), Receiver(receiver))
+}
+
+/// The sending half of a channel, can be cloned to send from multiple threads.
+pub struct Sender<T>(mpsc::Sender<ThreadCell<T>>);
+
+impl<T: Send> Sender<T> {
+    /// Sends a cell owned by the current thread (or disowned) to the receiver. When the
+    /// receiver is gone the cell is returned in the error, still owned by the current thread.
+    /// Pinned and shared values stay so.
+    ///
+    /// # Errors
+    ///
+    /// The receiver was dropped.
+    ///
+    /// # Panics
+    ///
+    /// Another thread owns the cell or it is frozen, frozen cells can never be taken again.
+    pub fn send(&self, cell: ThreadCell<T>) -> Result<(), SendError<ThreadCell<T>>> {
+        let current = CurrentThread::current().get();
+        let word = OwnerWord::load(&cell.thread_id, Ordering::Relaxed);
+        assert!(
+            word != FROZEN,
+            "Thread can not send ThreadCell, it is frozen"
+        );
+        let owner = word & !FLAGS;
+        assert!(
+            owner == 0 || owner == current,
+            "Thread has no access to ThreadCell"
+        );
+        diagnostics::forget(cell.addr(), owner);
+        // Disowned while in the channel, thus dropping a channel with queued cells is fine.
+        let sticky = word & STICKY;
+        OwnerWord::store(&cell.thread_id, sticky, Ordering::Relaxed);
+        self.0.send(cell).map_err(|SendError(cell)| {
+            OwnerWord::store(&cell.thread_id, current | sticky, Ordering::Relaxed);
+            SendError(cell)
+        })
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Sender(self.0.clone())
+    }
+}
+
+/// The receiving half of a channel.
+pub struct Receiver<T>(mpsc::Receiver<ThreadCell<T>>);
+
+impl<T: Send> Receiver<T> {
+    /// Waits for a cell and returns it owned by the current thread.
+    ///
+    /// # Errors
+    ///
+    /// All senders were dropped and the channel is empty.
+    pub fn recv(&self) -> Result<ThreadCell<T>, RecvError> {
+        self.0.recv().map(take)
+    }
+
+    /// Returns a cell owned by the current thread when one is available.
+    ///
+    /// # Errors
+    ///
+    /// The channel is empty or all senders were dropped.
+    pub fn try_recv(&self) -> Result<ThreadCell<T>, TryRecvError> {
+        self.0.try_recv().map(take)
+    }
+
+    /// Waits up to `timeout` for a cell and returns it owned by the current thread.
+    ///
+    /// # Errors
+    ///
+    /// The timeout elapsed or all senders were dropped and the channel is empty.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<ThreadCell<T>, RecvTimeoutError> {
+        self.0.recv_timeout(timeout).map(take)
+    }
+}
+
+/// Makes the current thread the owner of a received cell. The channel synchronizes with the
+/// sender, thus a relaxed store suffices.
+fn take<T>(cell: ThreadCell<T>) -> ThreadCell<T> {
+    let sticky = OwnerWord::load(&cell.thread_id, Ordering::Relaxed) & STICKY;
+    OwnerWord::store(
+        &cell.thread_id,
+        CurrentThread::current().get() | sticky,
+        Ordering::Relaxed,
+    );
+    cell
+}
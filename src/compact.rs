@@ -0,0 +1,73 @@
+//! Compact thread ids for narrow ownership words.
+//!
+//! The default `CurrentThread` identity hands out ever increasing 64 bit thread ids. On
+//! targets without 64 bit atomics or when memory is tight a `SmallThreadCell` uses an
+//! `AtomicU32` instead. Its thread ids are recycled when a thread exits, thus they stay small
+//! as long as not too many threads are alive at the same time.
+//!
+//! Note that when a thread exits while still owning a `SmallThreadCell` (acquire/release
+//! without a guard) the next thread that gets the recycled id becomes the owner of that cell.
+//! Same as with `steal()` this is memory safe since the exited thread can't access the cell
+//! anymore, but the value may be in a inconsistent state.
+
+use std::any::TypeId;
+use std::num::NonZeroU64;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use crate::{deferred, OwnerCell, OwnerIdentity, OwnerWord};
+
+/// A cell that can be owned by a single thread or none at all, storing the owner in an
+/// `AtomicU32`.
+pub type SmallThreadCell<T> = OwnerCell<T, CompactThread>;
+
+/// Identifies owners by their OS thread using small, recycled ids.
+pub struct CompactThread;
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+static FREE_IDS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Holds the id of a thread and puts it back to the free list when the thread exits.
+struct CompactId(NonZeroU64);
+
+impl CompactId {
+    fn new() -> Self {
+        let id = FREE_IDS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop()
+            .unwrap_or_else(|| NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        assert!(
+            id != 0 && u64::from(id) <= <AtomicU32 as OwnerWord>::MAX_ID,
+            "too many threads for SmallThreadCell"
+        );
+        CompactId(NonZeroU64::new(u64::from(id)).unwrap())
+    }
+}
+
+impl Drop for CompactId {
+    fn drop(&mut self) {
+        // the mailbox must not be inherited by the next thread
+        deferred::exited(TypeId::of::<CompactThread>(), self.0.get(), true);
+        #[cfg(not(target_has_atomic = "64"))]
+        deferred::exited(TypeId::of::<crate::CurrentThread>(), self.0.get(), true);
+        // The mutex establishes the happens-before relation to the next user of this id.
+        FREE_IDS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(self.0.get() as u32);
+    }
+}
+
+unsafe impl OwnerIdentity for CompactThread {
+    type Word = AtomicU32;
+
+    #[inline]
+    #[mutants::skip]
+    fn current() -> NonZeroU64 {
+        thread_local!(static COMPACT_ID: CompactId = CompactId::new());
+        COMPACT_ID
+            .try_with(|id| id.0)
+            .expect("SmallThreadCell accessed while the thread exits")
+    }
+}
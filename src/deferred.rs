@@ -0,0 +1,443 @@
+//! Work deferred to the owner of a cell: dropping values whose `DeferredCell` got dropped by
+//! a thread not owning it and running closures posted to the owner.
+//!
+//! Each owner that gets work sent has its own mailbox. The owner drops the values the next
+//! time it acquires a cell or calls `run_deferred_drops()` and runs the closures the next
+//! time it releases a cell or calls `poll_mailbox()`. Closures for cells in transit between
+//! threads go to owner 0 and are run by the next thread releasing a cell. Work is not done
+//! while the thread is unwinding. Panics of the work are caught, the first one is resumed
+//! after the remaining work was done.
+//!
+//! The mailbox of a thread is drained when the thread exits, panics of this work are lost.
+//! Work for other owners which never acquire or release a cell again (e.g. finished tasks or
+//! threads that exited before the work was queued) is leaked. Exited threads keep a closed
+//! mailbox which leaks work sent to them right away, thus it is not counted as queued.
+//!
+//! Acquiring and releasing cells only reads the global counters as long as nothing is
+//! queued, the thread local mailbox slots are looked at only when there is work.
+
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{CurrentThread, OwnerIdentity};
+
+/// A boxed value and the function consuming it.
+struct Deferred {
+    value: *mut (),
+    run: unsafe fn(*mut ()),
+    // closures are run when the owner releases a cell, values are dropped when it acquires one
+    message: bool,
+}
+
+// SAFETY: the value is only moved to the mailbox by other threads, only the owner consumes it
+unsafe impl Send for Deferred {}
+
+/// The queued work of one owner.
+#[derive(Default)]
+struct Mailbox {
+    /// Number of queued values and closures, lets the owner skip locking the queue when there
+    /// are none.
+    drops: AtomicUsize,
+    messages: AtomicUsize,
+    /// Set once the owner exited.
+    closed: AtomicBool,
+    queue: Mutex<Vec<Deferred>>,
+}
+
+/// The work a drain takes from a mailbox.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Work {
+    Drops,
+    Messages,
+    All,
+}
+
+impl Work {
+    fn takes(self, deferred: &Deferred) -> bool {
+        match self {
+            Work::Drops => !deferred.message,
+            Work::Messages => deferred.message,
+            Work::All => true,
+        }
+    }
+}
+
+/// The payload of a panic caught while doing work.
+type Panic = Box<dyn Any + Send>;
+
+/// The mailboxes by identity and owner. Owner 0 holds the closures for cells in transit
+/// between threads. Compact thread ids are recycled, their closed mailboxes are removed, the
+/// others stay to leak work sent to them.
+static MAILBOXES: Mutex<BTreeMap<(TypeId, u64), Arc<Mailbox>>> = Mutex::new(BTreeMap::new());
+
+/// Number of mailboxes of owners in `MAILBOXES`, owners only look for theirs when there are
+/// any.
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts created mailboxes, owners without one look again only when this changed.
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of values queued to any owner, nobody looks for its mailbox when acquiring a cell
+/// while this is 0.
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of closures queued to any owner including 0, nobody looks for its mailbox when
+/// releasing a cell while this is 0.
+static MESSAGES: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of closures queued to owner 0 of any identity, these are run at any release.
+static OWNERLESS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static OWNERS: Owners = const { Owners(RefCell::new(Vec::new())) };
+    // no work is done while doing work, owners acquiring cells there would recurse
+    static RUNNING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The mailboxes of the owners the current thread was, one per identity.
+struct Owners(RefCell<Vec<Slot>>);
+
+struct Slot {
+    identity: TypeId,
+    owner: u64,
+    // value of `CREATED` when the mailbox was looked up
+    created: usize,
+    mailbox: Option<Arc<Mailbox>>,
+}
+
+impl Slot {
+    fn lookup(&mut self) {
+        // pairs with creating mailboxes, a mailbox created before is in `MAILBOXES`
+        self.created = CREATED.load(Ordering::Acquire);
+        self.mailbox = if LIVE.load(Ordering::Relaxed) == 0 {
+            None
+        } else {
+            lock(&MAILBOXES).get(&(self.identity, self.owner)).cloned()
+        };
+    }
+
+    /// Returns the mailbox when the work `pending` selects is queued in it.
+    fn pending(&mut self, pending: fn(&Mailbox) -> &AtomicUsize) -> Option<&Arc<Mailbox>> {
+        let stale = match &self.mailbox {
+            Some(mailbox) => mailbox.closed.load(Ordering::Relaxed),
+            None => CREATED.load(Ordering::Relaxed) != self.created,
+        };
+        if stale {
+            self.lookup();
+        }
+        self.mailbox
+            .as_ref()
+            .filter(|mailbox| pending(mailbox).load(Ordering::Relaxed) != 0)
+    }
+}
+
+/// Drains the mailbox of the current thread when it exits. Compact thread ids are recycled,
+/// their mailboxes are drained before the id is freed.
+impl Drop for Owners {
+    fn drop(&mut self) {
+        #[cfg(target_has_atomic = "64")]
+        for slot in self.0.get_mut() {
+            if slot.identity == TypeId::of::<CurrentThread>() {
+                exited(slot.identity, slot.owner, false);
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Drops a value boxed by `defer_drop()`.
+unsafe fn drop_boxed<T>(value: *mut ()) {
+    drop(Box::from_raw(value.cast::<T>()));
+}
+
+/// Moves the value out of a cell that is dropped by a non-owner and queues it to be dropped
+/// by `owner`.
+pub(crate) unsafe fn defer_drop<T: 'static, I: OwnerIdentity>(value: *mut (), owner: u64) {
+    let value = Box::into_raw(Box::new(core::ptr::read(value.cast::<T>()))).cast::<()>();
+    let mut mailboxes = lock(&MAILBOXES);
+    let mailbox = open(&mut mailboxes, TypeId::of::<I>(), owner);
+    let mut queue = lock(&mailbox.queue);
+    if mailbox.closed.load(Ordering::Relaxed) {
+        // the owner exited, the value can not be dropped anywhere else
+        return;
+    }
+    queue.push(Deferred {
+        value,
+        run: drop_boxed::<T>,
+        message: false,
+    });
+    mailbox.drops.fetch_add(1, Ordering::Relaxed);
+    DROPS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the mailbox of `owner`, creating it when necessary.
+fn open(
+    mailboxes: &mut BTreeMap<(TypeId, u64), Arc<Mailbox>>,
+    identity: TypeId,
+    owner: u64,
+) -> Arc<Mailbox> {
+    mailboxes
+        .entry((identity, owner))
+        .or_insert_with(|| {
+            if owner != 0 {
+                LIVE.fetch_add(1, Ordering::Relaxed);
+            }
+            // Counted before the owner is checked, pairs with the fence in `released()`
+            CREATED.fetch_add(1, Ordering::SeqCst);
+            Arc::default()
+        })
+        .clone()
+}
+
+/// Calls a message boxed by `send()`.
+unsafe fn call_boxed<M>(value: *mut ()) {
+    let message = Box::from_raw(value.cast::<(M, fn(M))>());
+    (message.1)(message.0);
+}
+
+/// Queues `message` to be passed to `run` by the owner `owner` returns. The message is
+/// queued only when `owner` returns the same owner before and after it was counted, thus
+/// the owner can not miss it. Returns the message when `owner` returns `None` or changed.
+///
+/// # Safety
+///
+/// Anything `message` refers to must outlive its queueing. It is sent to the owner even when
+/// it is not `Send`.
+pub(crate) unsafe fn send<I: OwnerIdentity, M>(
+    owner: impl Fn() -> Option<u64>,
+    message: M,
+    run: fn(M),
+) -> Result<(), M> {
+    let Some(expected) = owner() else {
+        return Err(message);
+    };
+    let mut mailboxes = lock(&MAILBOXES);
+    let mailbox = open(&mut mailboxes, TypeId::of::<I>(), expected);
+    let mut queue = lock(&mailbox.queue);
+    if mailbox.closed.load(Ordering::Relaxed) {
+        // the owner exited, nobody will ever run the message
+        core::mem::forget(message);
+        return Ok(());
+    }
+    // Counted before the owner is checked again, pairs with the fence in `released()`
+    mailbox.messages.fetch_add(1, Ordering::SeqCst);
+    MESSAGES.fetch_add(1, Ordering::SeqCst);
+    if expected == 0 {
+        OWNERLESS.fetch_add(1, Ordering::SeqCst);
+    }
+    if owner() != Some(expected) {
+        mailbox.messages.fetch_sub(1, Ordering::Relaxed);
+        MESSAGES.fetch_sub(1, Ordering::Relaxed);
+        if expected == 0 {
+            OWNERLESS.fetch_sub(1, Ordering::Relaxed);
+        }
+        return Err(message);
+    }
+    queue.push(Deferred {
+        value: Box::into_raw(Box::new((message, run))).cast(),
+        run: call_boxed::<M>,
+        message: true,
+    });
+    Ok(())
+}
+
+/// Calls `f` with the slot of the current owner of identity `I`. Returns `None` when the
+/// thread exits.
+#[inline]
+fn with_slot<I: OwnerIdentity, R>(owner: u64, f: impl FnOnce(&mut Slot) -> R) -> Option<R> {
+    OWNERS
+        .try_with(|owners| {
+            let mut slots = owners.0.borrow_mut();
+            let identity = TypeId::of::<I>();
+            let index = match slots.iter().position(|slot| slot.identity == identity) {
+                Some(index) => index,
+                None => {
+                    slots.push(Slot {
+                        identity,
+                        owner: 0,
+                        created: 0,
+                        mailbox: None,
+                    });
+                    slots.len() - 1
+                }
+            };
+            let slot = &mut slots[index];
+            if slot.owner != owner {
+                slot.owner = owner;
+                slot.lookup();
+            }
+            f(slot)
+        })
+        .ok()
+}
+
+/// Makes sure the mailbox of the current owner of identity `I` is drained when its thread
+/// exits, even when it never acquires a cell.
+pub(crate) fn attach<I: OwnerIdentity>() {
+    with_slot::<I, _>(I::current().get(), |_| {});
+}
+
+/// Returns true when the current thread may do queued work now.
+fn may_run() -> bool {
+    !std::thread::panicking() && !RUNNING.try_with(Cell::get).unwrap_or(true)
+}
+
+/// Drops the values queued for the current owner of identity `I`. Called at acquire points
+/// before the cell is taken.
+///
+/// # Panics
+///
+/// Resumes the first panic of the drops.
+#[inline]
+pub(crate) fn run<I: OwnerIdentity>() {
+    if DROPS.load(Ordering::Relaxed) != 0 {
+        run_drops::<I>();
+    }
+}
+
+#[cold]
+fn run_drops<I: OwnerIdentity>() {
+    if !may_run() {
+        return;
+    }
+    if let Some(Some(mailbox)) = with_slot::<I, _>(I::current().get(), |slot| {
+        slot.pending(|mailbox| &mailbox.drops).cloned()
+    }) {
+        if let (_, Some(payload)) = drain(&mailbox, Work::Drops) {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+/// Runs the closures queued for the current owner of identity `I` and for cells in transit
+/// after it released a cell. Anything sent while the cell was still owned is seen.
+///
+/// # Panics
+///
+/// Resumes the first panic of the closures.
+#[inline]
+pub(crate) fn released<I: OwnerIdentity>() {
+    fence(Ordering::SeqCst);
+    if let Some(payload) = poll::<I>() {
+        panic::resume_unwind(payload);
+    }
+}
+
+/// Runs the closures queued for the current owner of identity `I` and for cells in transit.
+/// Returns the first panic of the closures.
+#[inline]
+pub(crate) fn poll<I: OwnerIdentity>() -> Option<Panic> {
+    if MESSAGES.load(Ordering::Relaxed) == 0 {
+        return None;
+    }
+    run_messages::<I>()
+}
+
+#[cold]
+fn run_messages<I: OwnerIdentity>() -> Option<Panic> {
+    if !may_run() {
+        return None;
+    }
+    let mut panicked = None;
+    if let Some(Some(mailbox)) = with_slot::<I, _>(I::current().get(), |slot| {
+        slot.pending(|mailbox| &mailbox.messages).cloned()
+    }) {
+        panicked = drain(&mailbox, Work::Messages).1;
+    }
+    if OWNERLESS.load(Ordering::Relaxed) != 0 {
+        let mailbox = lock(&MAILBOXES).get(&(TypeId::of::<I>(), 0)).cloned();
+        if let Some(mailbox) = mailbox {
+            let (done, payload) = drain(&mailbox, Work::Messages);
+            OWNERLESS.fetch_sub(done, Ordering::Relaxed);
+            panicked = panicked.or(payload);
+        }
+    }
+    panicked
+}
+
+/// Does the `work` in `mailbox`. Returns the number of closures run and the first panic.
+#[cold]
+fn drain(mailbox: &Mailbox, work: Work) -> (usize, Option<Panic>) {
+    let taken: Vec<Deferred> = {
+        let mut queue = lock(&mailbox.queue);
+        if work == Work::All {
+            core::mem::take(&mut *queue)
+        } else {
+            let (taken, kept) = core::mem::take(&mut *queue)
+                .into_iter()
+                .partition(|deferred| work.takes(deferred));
+            *queue = kept;
+            taken
+        }
+    };
+    let messages = taken.iter().filter(|deferred| deferred.message).count();
+    let drops = taken.len() - messages;
+    mailbox.messages.fetch_sub(messages, Ordering::Relaxed);
+    mailbox.drops.fetch_sub(drops, Ordering::Relaxed);
+    MESSAGES.fetch_sub(messages, Ordering::Relaxed);
+    DROPS.fetch_sub(drops, Ordering::Relaxed);
+    let running = RUNNING.try_with(|running| running.replace(true)).ok();
+    let mut panicked = None;
+    // the lock is released, destructors and messages may use cells again
+    for deferred in taken {
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+            (deferred.run)(deferred.value);
+        })) {
+            panicked.get_or_insert(payload);
+        }
+    }
+    if let Some(running) = running {
+        let _ = RUNNING.try_with(|cell| cell.set(running));
+    }
+    (messages, panicked)
+}
+
+/// Closes the mailbox of an owner whose thread exits and does its remaining work, panics of
+/// it are lost. The mailbox of a `recycled` id is removed, the next thread with that id gets
+/// a new one.
+pub(crate) fn exited(identity: TypeId, owner: u64, recycled: bool) {
+    let mut mailboxes = lock(&MAILBOXES);
+    let mailbox = if recycled {
+        mailboxes.remove(&(identity, owner))
+    } else {
+        mailboxes.get(&(identity, owner)).cloned()
+    };
+    drop(mailboxes);
+    if let Some(mailbox) = mailbox {
+        LIVE.fetch_sub(1, Ordering::Relaxed);
+        // sending to the mailbox checks this under the lock, nothing is queued anymore
+        let queue = lock(&mailbox.queue);
+        mailbox.closed.store(true, Ordering::Relaxed);
+        drop(queue);
+        drain(&mailbox, Work::All);
+    }
+}
+
+/// Drops the values of deferred drop cells which other threads dropped while the current
+/// thread owned them. This happens implicitly when the thread acquires any cell.
+///
+/// # Panics
+///
+/// Resumes the first panic of the drops after the remaining values were dropped.
+pub fn run_deferred_drops() {
+    run::<CurrentThread>();
+}
+
+/// Runs the closures other threads posted to cells owned by the current thread, see
+/// `ThreadCell::post()`. This happens implicitly when the thread releases any cell.
+///
+/// # Panics
+///
+/// Resumes the first panic of the closures after the remaining closures ran.
+pub fn poll_mailbox() {
+    if let Some(payload) = poll::<CurrentThread>() {
+        panic::resume_unwind(payload);
+    }
+}
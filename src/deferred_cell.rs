@@ -0,0 +1,94 @@
+//! Cells whose value is dropped by the owner when another thread drops them.
+
+use core::mem::{self, ManuallyDrop};
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::Ordering;
+
+use crate::{
+    deferred, diagnostics, CurrentThread, OwnerCell, OwnerIdentity, OwnerWord, FLAGS, PINNED_BIT,
+};
+
+/// A `ThreadCell` with deferred drop. Dropping a `ThreadCell` owned by another thread
+/// panics, a `DeferredCell` queues its value to the owner instead which drops it the next
+/// time it acquires a cell or calls `run_deferred_drops()`.
+///
+/// Derefs to the underlying `ThreadCell` for acquire, release and access.
+pub type DeferredCell<T> = DeferredOwnerCell<T, CurrentThread>;
+
+/// A `OwnerCell` with deferred drop, the generic type behind `DeferredCell`.
+pub struct DeferredOwnerCell<T: ?Sized + 'static, I: OwnerIdentity> {
+    // Moves the value to the owners mailbox, type erased to allow unsizing
+    defer: unsafe fn(*mut (), u64),
+    // Must be the last field for unsizing
+    cell: ManuallyDrop<OwnerCell<T, I>>,
+}
+
+impl<T: 'static, I: OwnerIdentity> DeferredOwnerCell<T, I> {
+    const_fn! {
+        /// Creates a `DeferredCell` that is not owned by any thread. This is a const fn which
+        /// allows static construction.
+        pub const fn new_disowned(data: T) -> Self {
+            DeferredOwnerCell {
+                defer: deferred::defer_drop::<T, I>,
+                cell: ManuallyDrop::new(OwnerCell::new_disowned(data)),
+            }
+        }
+    }
+
+    /// Creates a `DeferredCell` that is owned by the current thread.
+    pub fn new_owned(data: T) -> Self {
+        deferred::attach::<I>();
+        DeferredOwnerCell {
+            defer: deferred::defer_drop::<T, I>,
+            cell: ManuallyDrop::new(OwnerCell::new_owned(data)),
+        }
+    }
+
+    /// Consumes a owned or disowned cell and returns its value.
+    ///
+    /// # Panics
+    ///
+    /// Another thread owns the cell.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY: `this` is not dropped, the cell is moved out only once
+        unsafe { ManuallyDrop::take(&mut this.cell) }.into_inner()
+    }
+}
+
+impl<T: ?Sized, I: OwnerIdentity> Deref for DeferredOwnerCell<T, I> {
+    type Target = OwnerCell<T, I>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cell
+    }
+}
+
+impl<T: ?Sized, I: OwnerIdentity> DerefMut for DeferredOwnerCell<T, I> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cell
+    }
+}
+
+/// Destroys a `DeferredCell`. When another thread owns it the value is queued to the owner.
+///
+/// # Panics
+///
+/// Another thread owns the cell and the value is pinned, it must be dropped in place.
+impl<T: ?Sized, I: OwnerIdentity> Drop for DeferredOwnerCell<T, I> {
+    fn drop(&mut self) {
+        let word = self.cell.thread_id.load(Ordering::Acquire);
+        let owner = word & !FLAGS;
+        if owner != 0 && owner != I::current().get() && word & PINNED_BIT == 0 {
+            diagnostics::forget(self.cell.addr(), owner);
+            if mem::needs_drop::<T>() {
+                // SAFETY: `defer` moves the value out, the cell is not used anymore
+                unsafe { (self.defer)(self.cell.data.get().cast(), owner) };
+            }
+        } else {
+            // SAFETY: the cell is dropped only once
+            unsafe { ManuallyDrop::drop(&mut self.cell) };
+        }
+    }
+}
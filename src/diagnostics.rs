@@ -0,0 +1,167 @@
+//! Bookkeeping about where `ThreadCells` got acquired. With the `diagnostics` feature in
+//! debug builds every successful acquisition records its caller location (and a backtrace
+//! when enabled by `RUST_BACKTRACE`/`RUST_LIB_BACKTRACE`) in a side table keyed by the address
+//! of the cell. This is only used to produce more helpful panic messages when a thread fails
+//! to acquire a cell. It is opt-in since it locks a global table on every acquisition.
+//!
+//! Further each thread keeps a stack of the ranked cells it holds. Acquiring a ranked cell
+//! whose rank is not higher than the highest rank already held is a lock order violation
+//! which may deadlock when another thread acquires in the opposite order. Such violations
+//! panic, much like lockdep does in the Linux kernel.
+//!
+//! In release builds and without the `std` feature all of this compiles to nothing.
+
+#[cfg(all(debug_assertions, feature = "std"))]
+mod imp {
+    use std::cell::RefCell;
+    use std::panic::Location;
+
+    struct Held {
+        rank: u32,
+        cell: usize,
+        location: &'static Location<'static>,
+    }
+
+    thread_local!(static HELD: RefCell<Vec<Held>> = const { RefCell::new(Vec::new()) });
+
+    /// Panics when acquiring a cell with the given rank violates the lock order.
+    #[track_caller]
+    pub(crate) fn check_rank(rank: Option<u32>) {
+        let Some(rank) = rank else { return };
+        let _ = HELD.try_with(|held| {
+            if let Some(highest) = held.borrow().iter().max_by_key(|held| held.rank) {
+                assert!(
+                    highest.rank < rank,
+                    "ThreadCell lock order violation: acquiring rank {rank} while holding rank {} acquired at {}",
+                    highest.rank,
+                    highest.location
+                );
+            }
+        });
+    }
+
+    #[track_caller]
+    pub(crate) fn record(cell: usize, owner: u64, rank: Option<u32>) {
+        // closures do not inherit `#[track_caller]`
+        let location = Location::caller();
+        if let Some(rank) = rank {
+            let _ = HELD.try_with(|held| {
+                held.borrow_mut().push(Held {
+                    rank,
+                    cell,
+                    location,
+                });
+            });
+        }
+        table::record(cell, owner, location);
+    }
+
+    pub(crate) fn forget(cell: usize, owner: u64) {
+        let _ = HELD.try_with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(pos) = held.iter().rposition(|held| held.cell == cell) {
+                held.remove(pos);
+            }
+        });
+        table::forget(cell, owner);
+    }
+
+    pub(crate) use table::describe;
+
+    #[cfg(feature = "diagnostics")]
+    mod table {
+        use std::backtrace::{Backtrace, BacktraceStatus};
+        use std::collections::BTreeMap;
+        use std::fmt::Write;
+        use std::panic::Location;
+        use std::sync::{Mutex, PoisonError};
+
+        struct Acquisition {
+            owner: u64,
+            thread: Option<String>,
+            location: &'static Location<'static>,
+            backtrace: Backtrace,
+        }
+
+        static ACQUISITIONS: Mutex<BTreeMap<usize, Acquisition>> = Mutex::new(BTreeMap::new());
+
+        pub(super) fn record(cell: usize, owner: u64, location: &'static Location<'static>) {
+            let acquisition = Acquisition {
+                owner,
+                thread: std::thread::current().name().map(String::from),
+                location,
+                backtrace: Backtrace::capture(),
+            };
+            ACQUISITIONS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(cell, acquisition);
+        }
+
+        pub(super) fn forget(cell: usize, owner: u64) {
+            let mut acquisitions = ACQUISITIONS.lock().unwrap_or_else(PoisonError::into_inner);
+            if acquisitions.get(&cell).is_some_and(|a| a.owner == owner) {
+                acquisitions.remove(&cell);
+            }
+        }
+
+        pub(crate) fn describe(cell: usize, owner: u64) -> String {
+            let acquisitions = ACQUISITIONS.lock().unwrap_or_else(PoisonError::into_inner);
+            // The entry may be stale when the cell was moved while owned, only trust it when
+            // it names the same owner.
+            match acquisitions.get(&cell) {
+                Some(acquisition) if acquisition.owner == owner => {
+                    let mut message = match &acquisition.thread {
+                        Some(name) => format!(": already held by thread `{name}`"),
+                        None => String::from(": already held by an unnamed thread"),
+                    };
+                    let _ = write!(message, ", acquired at {}", acquisition.location);
+                    if acquisition.backtrace.status() == BacktraceStatus::Captured {
+                        let _ = write!(
+                            message,
+                            "\nacquisition backtrace:\n{}",
+                            acquisition.backtrace
+                        );
+                    }
+                    message
+                }
+                _ => String::new(),
+            }
+        }
+    }
+
+    #[cfg(not(feature = "diagnostics"))]
+    mod table {
+        use std::panic::Location;
+
+        #[inline(always)]
+        pub(super) fn record(_cell: usize, _owner: u64, _location: &'static Location<'static>) {}
+
+        #[inline(always)]
+        pub(super) fn forget(_cell: usize, _owner: u64) {}
+
+        #[inline(always)]
+        pub(crate) fn describe(_cell: usize, _owner: u64) -> &'static str {
+            ""
+        }
+    }
+}
+
+#[cfg(not(all(debug_assertions, feature = "std")))]
+mod imp {
+    #[inline(always)]
+    pub(crate) fn check_rank(_rank: Option<u32>) {}
+
+    #[inline(always)]
+    pub(crate) fn record(_cell: usize, _owner: u64, _rank: Option<u32>) {}
+
+    #[inline(always)]
+    pub(crate) fn forget(_cell: usize, _owner: u64) {}
+
+    #[inline(always)]
+    pub(crate) fn describe(_cell: usize, _owner: u64) -> &'static str {
+        ""
+    }
+}
+
+pub(crate) use imp::{check_rank, describe, forget, record};
@@ -0,0 +1,180 @@
+//! Identities of the owners of cells.
+
+use core::num::NonZeroU64;
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::AtomicPtr;
+#[cfg(not(target_has_atomic = "64"))]
+use core::sync::atomic::AtomicU32;
+#[cfg(all(not(loom), target_has_atomic = "64"))]
+use core::sync::atomic::AtomicU64;
+#[cfg(loom)]
+use loom::sync::atomic::AtomicU64;
+
+use crate::OwnerWord;
+
+/// Identifies the owners of an `OwnerCell`. The default for `ThreadCell` is `CurrentThread`
+/// which identifies OS threads. Other implementations can key ownership by green threads,
+/// async tasks, workers of a pool or a test controlled fake.
+///
+/// # Safety
+///
+/// `current()` must return an id not bigger than `Word::MAX_ID`. The same id must never be
+/// returned to two threads running concurrently, otherwise both could access the same cell
+/// at the same time. When an id is handed over from one thread to another, there must be a
+/// happens-before relation between them (as with any synchronization primitive).
+pub unsafe trait OwnerIdentity: 'static {
+    /// The atomic type storing the owner of a cell.
+    type Word: OwnerWord;
+
+    /// Returns the id of the current owner.
+    fn current() -> NonZeroU64;
+}
+
+/// Identifies owners by the OS thread they run on. This is the identity used by
+/// `ThreadCell`. Without the `std` feature thread ids come from the source registered with
+/// `set_thread_id_source()`.
+pub struct CurrentThread;
+
+unsafe impl OwnerIdentity for CurrentThread {
+    #[cfg(any(loom, target_has_atomic = "64"))]
+    type Word = AtomicU64;
+    #[cfg(not(any(loom, target_has_atomic = "64")))]
+    type Word = AtomicU32;
+
+    #[inline(always)]
+    #[mutants::skip]
+    fn current() -> NonZeroU64 {
+        current_thread_id()
+    }
+}
+
+/// A unique identifier for every thread.
+#[cfg(all(
+    feature = "std",
+    any(loom, not(feature = "nightly_thread_id_value")),
+    target_has_atomic = "64"
+))]
+struct ThreadId(NonZeroU64);
+
+#[cfg(all(
+    feature = "std",
+    any(loom, not(feature = "nightly_thread_id_value")),
+    target_has_atomic = "64"
+))]
+impl ThreadId {
+    #[inline]
+    #[must_use]
+    #[mutants::skip]
+    fn current() -> ThreadId {
+        #[cfg(not(loom))]
+        thread_local!(static THREAD_ID: NonZeroU64 = {
+            static COUNTER: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(1);
+            ThreadId::next(&COUNTER)
+        });
+        // Loom runs all modelled threads on one OS thread, thus needs its own thread locals.
+        #[cfg(loom)]
+        loom::thread_local!(static THREAD_ID: NonZeroU64 = {
+            loom::lazy_static! {
+                static ref COUNTER: AtomicU64 = AtomicU64::new(1);
+            }
+            ThreadId::next(&COUNTER)
+        });
+        THREAD_ID.with(|&x| ThreadId(x))
+    }
+
+    #[mutants::skip]
+    fn next(counter: &AtomicU64) -> NonZeroU64 {
+        let id =
+            NonZeroU64::new(counter.fetch_add(1, core::sync::atomic::Ordering::Relaxed)).unwrap();
+        assert!(
+            id.get() <= <CurrentThread as OwnerIdentity>::Word::MAX_ID,
+            "too many threads"
+        );
+        id
+    }
+
+    #[inline(always)]
+    #[must_use]
+    #[mutants::skip]
+    fn as_u64(&self) -> NonZeroU64 {
+        self.0
+    }
+}
+
+#[test]
+#[cfg(all(
+    feature = "std",
+    not(any(loom, feature = "nightly_thread_id_value")),
+    target_has_atomic = "64"
+))]
+fn threadid() {
+    let main = ThreadId::current().as_u64().get();
+    let child = std::thread::spawn(|| ThreadId::current().as_u64().get())
+        .join()
+        .unwrap();
+
+    // just info, actual values are unspecified
+    println!("{main}, {child}");
+
+    assert_ne!(main, 0);
+    assert_ne!(main, child);
+}
+
+#[cfg(all(
+    feature = "std",
+    any(loom, not(feature = "nightly_thread_id_value")),
+    target_has_atomic = "64"
+))]
+#[mutants::skip]
+#[inline]
+fn current_thread_id() -> NonZeroU64 {
+    ThreadId::current().as_u64()
+}
+
+#[cfg(all(feature = "std", not(loom), feature = "nightly_thread_id_value"))]
+#[mutants::skip]
+#[inline]
+fn current_thread_id() -> NonZeroU64 {
+    std::thread::current().id().as_u64()
+}
+
+/// Without 64 bit atomics the compact thread ids are used.
+#[cfg(all(
+    feature = "std",
+    not(loom),
+    not(feature = "nightly_thread_id_value"),
+    not(target_has_atomic = "64")
+))]
+#[mutants::skip]
+#[inline]
+fn current_thread_id() -> NonZeroU64 {
+    crate::compact::CompactThread::current()
+}
+
+#[cfg(not(feature = "std"))]
+static THREAD_ID_SOURCE: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Registers the function that identifies the current thread when the crate is built without
+/// the `std` feature. This can be an RTOS task id, a hart id or the like. It must be
+/// registered before any `ThreadCell` is used.
+///
+/// # Safety
+///
+/// `source` must uphold the contract of `OwnerIdentity::current()`: ids must not be bigger
+/// than `OwnerWord::MAX_ID` and must never be the same for two concurrently running threads. The
+/// source must not be changed while any `ThreadCell` is owned.
+#[cfg(not(feature = "std"))]
+pub unsafe fn set_thread_id_source(source: fn() -> NonZeroU64) {
+    THREAD_ID_SOURCE.store(source as *mut (), core::sync::atomic::Ordering::Release);
+}
+
+#[cfg(not(feature = "std"))]
+#[mutants::skip]
+#[inline]
+fn current_thread_id() -> NonZeroU64 {
+    let source = THREAD_ID_SOURCE.load(core::sync::atomic::Ordering::Acquire);
+    assert!(!source.is_null(), "no thread id source registered");
+    // SAFETY: only ever set from a `fn() -> NonZeroU64` in `set_thread_id_source()`
+    let source: fn() -> NonZeroU64 = unsafe { core::mem::transmute(source) };
+    source()
+}
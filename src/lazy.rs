@@ -0,0 +1,146 @@
+//! Lazily initialized cells for statics whose values can't be constructed in a const context.
+
+use core::cell::{LazyCell, OnceCell};
+use core::ops::Deref;
+
+use crate::ThreadCell;
+
+/// A `ThreadCell` whose value is initialized on first access by the thread owning it.
+///
+/// Derefs to the underlying `ThreadCell` for acquire and release, its accessors return the
+/// `LazyCell` then. The methods here force the initialization and return the value itself.
+pub struct LazyThreadCell<T, F = fn() -> T>(ThreadCell<LazyCell<T, F>>);
+
+impl<T, F: FnOnce() -> T> LazyThreadCell<T, F> {
+    const_fn! {
+        /// Creates a disowned `LazyThreadCell` which calls `init` on the first access. This is
+        /// a const fn which allows static construction.
+        pub const fn new(init: F) -> Self {
+            LazyThreadCell(ThreadCell::new_disowned(LazyCell::new(init)))
+        }
+    }
+
+    /// Gets an immutable reference to the value, initializing it when necessary.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    #[inline]
+    pub fn get(&self) -> &T {
+        LazyCell::force(self.0.get())
+    }
+
+    /// Gets a mutable reference to the value, initializing it when necessary.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        LazyCell::force_mut(self.0.get_mut())
+    }
+
+    /// Tries to get an immutable reference to the value, initializing it when necessary.
+    /// Returns 'None' when the thread does not own the cell.
+    #[inline]
+    pub fn try_get(&self) -> Option<&T> {
+        self.0.try_get().map(LazyCell::force)
+    }
+
+    /// Runs a closure on the value with acquire/release, initializing it when necessary.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is already owned by another thread.
+    #[track_caller]
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.0.with(|lazy| f(LazyCell::force(lazy)))
+    }
+
+    /// Tries to run a closure on the value with acquire/release, initializing it when
+    /// necessary. Returns None when the cell is owned by another thread.
+    #[track_caller]
+    pub fn try_with<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.0.try_with(|lazy| f(LazyCell::force(lazy)))
+    }
+}
+
+impl<T, F> Deref for LazyThreadCell<T, F> {
+    type Target = ThreadCell<LazyCell<T, F>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A `ThreadCell` which can be written to only once, by the thread owning it.
+///
+/// Derefs to the underlying `ThreadCell` for acquire and release.
+pub struct ThreadOnceCell<T>(ThreadCell<OnceCell<T>>);
+
+impl<T> ThreadOnceCell<T> {
+    const_fn! {
+        /// Creates an empty, disowned `ThreadOnceCell`. This is a const fn which allows static
+        /// construction.
+        pub const fn new() -> Self {
+            ThreadOnceCell(ThreadCell::new_disowned(OnceCell::new()))
+        }
+    }
+
+    /// Gets the value, returns 'None' when it is not initialized yet.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        self.0.get().get()
+    }
+
+    /// Sets the value, returns it back as error when the cell is already initialized.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        self.0.get().set(value)
+    }
+
+    /// Gets the value, initializing it with `init` when necessary.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    pub fn get_or_init(&self, init: impl FnOnce() -> T) -> &T {
+        self.0.get().get_or_init(init)
+    }
+
+    /// Tries to get the value, initializing it with `init` when necessary. Returns 'None'
+    /// when the thread does not own the cell.
+    pub fn try_get_or_init(&self, init: impl FnOnce() -> T) -> Option<&T> {
+        Some(self.0.try_get()?.get_or_init(init))
+    }
+
+    /// Takes the value out of the cell, leaving it uninitialized.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    pub fn take(&mut self) -> Option<T> {
+        self.0.get_mut().take()
+    }
+}
+
+impl<T> Default for ThreadOnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for ThreadOnceCell<T> {
+    type Target = ThreadCell<OnceCell<T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
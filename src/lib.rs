@@ -7,74 +7,307 @@
 #![warn(missing_docs)]
 #![warn(rustdoc::missing_crate_level_docs)]
 #![cfg_attr(feature = "nightly_thread_id_value", feature(thread_id_value))]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+use core::any::TypeId;
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::panic::RefUnwindSafe;
+use core::pin::Pin;
+use core::sync::atomic::Ordering;
+#[cfg(feature = "std")]
+use core::time::Duration;
+use core::{cmp, fmt, mem, ptr};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// Defines a `const fn`, except when model checking with loom whose atomics can not be
+/// constructed in const context.
+macro_rules! const_fn {
+    ($(#[$attr:meta])* $vis:vis const fn $($rest:tt)*) => {
+        #[cfg(not(loom))]
+        $(#[$attr])*
+        $vis const fn $($rest)*
+
+        #[cfg(loom)]
+        $(#[$attr])*
+        $vis fn $($rest)*
+    };
+    ($(#[$attr:meta])* $vis:vis const unsafe fn $($rest:tt)*) => {
+        #[cfg(not(loom))]
+        $(#[$attr])*
+        $vis const unsafe fn $($rest)*
+
+        #[cfg(loom)]
+        $(#[$attr])*
+        $vis unsafe fn $($rest)*
+    };
+}
 
-use std::mem::ManuallyDrop;
-use std::ops::{Deref, DerefMut};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::{cmp, fmt, mem};
+#[cfg(feature = "std")]
+pub mod channel;
+#[cfg(feature = "std")]
+mod compact;
+#[cfg(feature = "std")]
+mod deferred;
+#[cfg(feature = "std")]
+mod deferred_cell;
+mod diagnostics;
+mod identity;
+mod lazy;
+#[cfg(feature = "std")]
+mod mailbox;
+// Without 64 bit atomics thread ids are recycled, a new thread would inherit the cells of
+// an exited one
+#[cfg(all(feature = "std", target_has_atomic = "64"))]
+mod main_thread;
+#[cfg(all(feature = "std", target_has_atomic = "64"))]
+mod pinned;
+mod refcell;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod shared;
+#[cfg(feature = "std")]
+mod spawn;
+mod static_cell;
+#[cfg(all(feature = "std", target_has_atomic = "64"))]
+pub mod task;
+mod unsend;
+#[cfg(feature = "std")]
+mod wait;
+mod word;
+
+#[cfg(feature = "std")]
+pub use channel::channel;
+#[cfg(feature = "std")]
+pub use compact::{CompactThread, SmallThreadCell};
+#[cfg(feature = "std")]
+pub use deferred::{poll_mailbox, run_deferred_drops};
+#[cfg(feature = "std")]
+pub use deferred_cell::{DeferredCell, DeferredOwnerCell};
+#[cfg(not(feature = "std"))]
+pub use identity::set_thread_id_source;
+pub use identity::{CurrentThread, OwnerIdentity};
+pub use lazy::{LazyThreadCell, ThreadOnceCell};
+#[cfg(all(feature = "std", target_has_atomic = "64"))]
+pub use main_thread::{is_main_thread, mark_main_thread, MainThreadCell};
+#[cfg(all(feature = "std", target_has_atomic = "64"))]
+pub use pinned::PinnedCell;
+pub use refcell::{Ref, RefMut, ThreadRefCell};
+pub use shared::SharedGuard;
+#[cfg(feature = "std")]
+pub use spawn::{spawn_scoped_with, spawn_with, JoinHandle, ScopedJoinHandle};
+#[cfg(all(feature = "std", target_has_atomic = "64"))]
+pub use task::TaskCell;
+pub use unsend::UnsendCell;
+#[cfg(feature = "std")]
+pub use wait::Deadlock;
+pub use word::OwnerWord;
 
 /// A cell that can be owned by a single thread or none at all.
-pub struct ThreadCell<T> {
-    data: ManuallyDrop<T>,
-    thread_id: AtomicU64,
+pub type ThreadCell<T> = OwnerCell<T, CurrentThread>;
+
+/// A cell that can be owned by a single owner or none at all. Owners are identified by the
+/// `OwnerIdentity` `I`. This is the generic type behind `ThreadCell`, all its semantics
+/// apply with 'thread' meaning whatever `I` identifies.
+pub struct OwnerCell<T: ?Sized, I: OwnerIdentity> {
+    thread_id: I::Word,
+    #[cfg(debug_assertions)]
+    rank: Option<u32>,
+    identity: PhantomData<fn() -> I>,
+    // Must be the last field for unsizing
+    data: UnsafeCell<ManuallyDrop<T>>,
 }
 
 // We use the highest bit of a thread id to indicate that we hold a guard
 const GUARD_BIT: u64 = i64::MAX as u64 + 1;
 
+// Together with the guard bit this marks an exclusive guard, which refuses `get()`
+const EXCLUSIVE_BIT: u64 = GUARD_BIT >> 1;
+
+// Set for good once the value was accessed pinned, from then on it must not be moved. Unlike
+// the other flags this is kept when the cell is released.
+const PINNED_BIT: u64 = GUARD_BIT >> 2;
+
+// Set for good once `get()` or `get_shared()` handed out a reference that may outlive the
+// ownership, from then on no exclusive guard is handed out anymore. Kept when the cell is
+// released like the pinned bit.
+const SHARED_BIT: u64 = GUARD_BIT >> 3;
+
+// Marks a cell read by shared guards, the id bits hold the number of readers instead of an
+// owner
+const READERS_BIT: u64 = GUARD_BIT >> 4;
+
+// The flags kept when a cell is released and taken again
+const STICKY: u64 = PINNED_BIT | SHARED_BIT;
+
+// A shared exclusive guard without owner marks a cell frozen by `freeze()`, every thread can
+// read it and nobody can take it anymore
+const FROZEN: u64 = GUARD_BIT | EXCLUSIVE_BIT | SHARED_BIT;
+
+// The exclusive bit alone marks a cell in transit from or to a thread spawned by
+// `spawn_with()`, it is neither owned nor disowned meanwhile
+#[cfg(feature = "std")]
+const TRANSIT: u64 = EXCLUSIVE_BIT;
+
+// All flag bits, these are never part of an owner id
+const FLAGS: u64 = GUARD_BIT | EXCLUSIVE_BIT | PINNED_BIT | SHARED_BIT | READERS_BIT;
+
 #[allow(clippy::non_send_fields_in_send_ty)]
-unsafe impl<T: Send> Send for ThreadCell<T> {}
-unsafe impl<T: Send> Sync for ThreadCell<T> {}
+unsafe impl<T: ?Sized + Send, I: OwnerIdentity> Send for OwnerCell<T, I> {}
+unsafe impl<T: ?Sized + Send, I: OwnerIdentity> Sync for OwnerCell<T, I> {}
+
+// The content is mutated through a shared reference only by an exclusive `GuardMut`, thus
+// the cell is unwind safe when its content is.
+impl<T: ?Sized + RefUnwindSafe, I: OwnerIdentity> RefUnwindSafe for OwnerCell<T, I> {}
+
+impl<T, I: OwnerIdentity> OwnerCell<T, I> {
+    const_fn! {
+        /// Creates a `ThreadCell` that is not owned by any thread. This is a const fn which
+        /// allows static construction of `ThreadCells`.
+        pub const fn new_disowned(data: T) -> Self {
+            Self {
+                data: UnsafeCell::new(ManuallyDrop::new(data)),
+                thread_id: word::disowned(),
+                #[cfg(debug_assertions)]
+                rank: None,
+                identity: PhantomData,
+            }
+        }
+    }
 
-impl<T> ThreadCell<T> {
-    /// Creates a `ThreadCell` that is not owned by any thread. This is a const fn which
-    /// allows static construction of `ThreadCells`.
-    pub const fn new_disowned(data: T) -> Self {
-        Self {
-            data: ManuallyDrop::new(data),
-            thread_id: AtomicU64::new(0),
+    const_fn! {
+        /// Creates a disowned `ThreadCell` with a rank for lock order checking. In debug
+        /// builds a thread must acquire ranked cells in strictly increasing rank order,
+        /// acquiring a cell whose rank is not higher than any ranked cell the thread already
+        /// holds panics. This catches inconsistent lock ordering which can deadlock. The
+        /// `try_*` variants are not checked since they never block. Release builds ignore
+        /// the rank. This is a const fn which allows static construction of
+        /// `ThreadCells`.
+        #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+        pub const fn new_disowned_ranked(data: T, rank: u32) -> Self {
+            Self {
+                data: UnsafeCell::new(ManuallyDrop::new(data)),
+                thread_id: word::disowned(),
+                #[cfg(debug_assertions)]
+                rank: Some(rank),
+                identity: PhantomData,
+            }
         }
     }
 
     /// Creates a `ThreadCell` that is owned by the current thread.
     pub fn new_owned(data: T) -> Self {
         Self {
-            data: ManuallyDrop::new(data),
-            thread_id: AtomicU64::new(current_thread_id()),
+            data: UnsafeCell::new(ManuallyDrop::new(data)),
+            thread_id: I::Word::new(I::current().get()),
+            #[cfg(debug_assertions)]
+            rank: None,
+            identity: PhantomData,
         }
     }
+}
 
+impl<T: ?Sized, I: OwnerIdentity> OwnerCell<T, I> {
     /// Takes the ownership of a cell.
     ///
     /// # Panics
     ///
     /// When the cell is already owned by this thread or it is owned by another thread.
+    #[track_caller]
     pub fn acquire(&self) {
-        self.thread_id
-            .compare_exchange(0, current_thread_id(), Ordering::Acquire, Ordering::Relaxed)
-            .expect("Thread can not acquire ThreadCell");
+        if let Err(owner) = self.take_ordered(I::current().get()) {
+            self.acquire_failed(owner);
+        }
     }
 
     /// Tries to take the ownership of a cell. Returns true when the ownership could be
     /// obtained or the cell was already owned by the current thread and false when the cell
     /// is owned by another thread.
+    #[track_caller]
     pub fn try_acquire(&self) -> bool {
         if self.is_acquired() {
             true
         } else {
-            self.thread_id
-                .compare_exchange(0, current_thread_id(), Ordering::Acquire, Ordering::Relaxed)
-                .is_ok()
+            self.try_take(I::current().get()).is_ok()
         }
     }
 
     /// Tries to take the ownership of a cell. Returns true when the ownership could be
     /// obtained and false when the cell is already owned or owned by another thread.
     /// Note that this fails when the cell is already owned (unlike `try_acquire()`).
+    #[track_caller]
     pub fn try_acquire_once(&self) -> bool {
-        self.thread_id
-            .compare_exchange(0, current_thread_id(), Ordering::Acquire, Ordering::Relaxed)
-            .is_ok()
+        self.try_take(I::current().get()).is_ok()
+    }
+
+    /// Like `try_take()` but checks the lock order first, for the acquisitions that panic or
+    /// block. Non-blocking tries can not deadlock and are not ordered.
+    #[inline]
+    #[track_caller]
+    fn take_ordered(&self, owner: u64) -> Result<(), u64> {
+        diagnostics::check_rank(self.rank());
+        self.try_take(owner)
+    }
+
+    /// Takes a disowned cell by setting its ownership word to `owner`. Returns the current
+    /// ownership word when the cell is not disowned.
+    #[inline]
+    #[track_caller]
+    fn try_take(&self, owner: u64) -> Result<(), u64> {
+        #[cfg(feature = "std")]
+        deferred::run::<I>();
+        let mut disowned = 0;
+        // pinned and shared values stay so under the new owner
+        while let Err(word) = self.thread_id.compare_exchange(
+            disowned,
+            owner | disowned,
+            Ordering::Acquire,
+            Ordering::Relaxed,
+        ) {
+            if word & !STICKY != 0 {
+                return Err(word);
+            }
+            disowned = word;
+        }
+        diagnostics::record(self.addr(), owner & !FLAGS, self.rank());
+        Ok(())
+    }
+
+    #[cold]
+    #[track_caller]
+    fn acquire_failed(&self, owner: u64) -> ! {
+        if owner == FROZEN {
+            panic!("Thread can not acquire ThreadCell, it is frozen");
+        }
+        if owner & READERS_BIT != 0 {
+            panic!(
+                "Thread can not acquire ThreadCell, it is read by {} shared guards",
+                owner & !FLAGS
+            );
+        }
+        panic!(
+            "Thread can not acquire ThreadCell{}",
+            diagnostics::describe(self.addr(), owner & !FLAGS)
+        );
+    }
+
+    /// The rank for lock order checking, always `None` in release builds.
+    #[inline(always)]
+    fn rank(&self) -> Option<u32> {
+        #[cfg(debug_assertions)]
+        return self.rank;
+        #[cfg(not(debug_assertions))]
+        return None;
+    }
+
+    /// The address of the cell, used as key for debugging side tables.
+    #[inline(always)]
+    fn addr(&self) -> usize {
+        self as *const Self as *const () as usize
     }
 
     /// Takes the ownership of a cell and returns a reference to its value.
@@ -82,20 +315,20 @@ impl<T> ThreadCell<T> {
     /// # Panics
     ///
     /// When the cell is owned by another thread.
+    #[track_caller]
     pub fn acquire_get(&self) -> &T {
         if !self.is_owned() {
             self.acquire();
         }
-        // Safety: we have it
-        unsafe { self.get_unchecked() }
+        self.share()
     }
 
     /// Tries to take the ownership of a cell and returns a reference to its value.
     /// Will return 'None' when the cell is owned by another thread.
+    #[track_caller]
     pub fn try_acquire_get(&self) -> Option<&T> {
         if self.try_acquire() {
-            // Safety: we have it
-            Some(unsafe { self.get_unchecked() })
+            Some(self.share())
         } else {
             None
         }
@@ -106,6 +339,7 @@ impl<T> ThreadCell<T> {
     /// # Panics
     ///
     /// When the cell is owned by another thread.
+    #[track_caller]
     pub fn acquire_get_mut(&mut self) -> &mut T {
         if !self.is_owned() {
             self.acquire();
@@ -116,6 +350,7 @@ impl<T> ThreadCell<T> {
 
     /// Tries to take the ownership of a cell and returns a mutable reference to its value.
     /// Will return 'None' when the cell is owned by another thread.
+    #[track_caller]
     pub fn try_acquire_get_mut(&mut self) -> Option<&mut T> {
         if self.try_acquire() {
             // Safety: we have it
@@ -131,15 +366,11 @@ impl<T> ThreadCell<T> {
     ///
     /// When the cell is owned by another thread.
     #[inline]
-    pub fn acquire_guard(&self) -> Guard<T> {
-        self.thread_id
-            .compare_exchange(
-                0,
-                current_thread_id() | GUARD_BIT,
-                Ordering::Acquire,
-                Ordering::Relaxed,
-            )
-            .expect("Thread can not acquire ThreadCell");
+    #[track_caller]
+    pub fn acquire_guard(&self) -> Guard<'_, T, I> {
+        if let Err(owner) = self.take_ordered(I::current().get() | GUARD_BIT) {
+            self.acquire_failed(owner);
+        }
         Guard(self)
     }
 
@@ -147,17 +378,9 @@ impl<T> ThreadCell<T> {
     /// dropped.  Returns `None` when self is owned by another thread.
     #[inline]
     #[mutants::skip]
-    pub fn try_acquire_guard(&self) -> Option<Guard<T>> {
-        if self
-            .thread_id
-            .compare_exchange(
-                0,
-                current_thread_id() | GUARD_BIT,
-                Ordering::Acquire,
-                Ordering::Relaxed,
-            )
-            .is_ok()
-        {
+    #[track_caller]
+    pub fn try_acquire_guard(&self) -> Option<Guard<'_, T, I>> {
+        if self.try_take(I::current().get() | GUARD_BIT).is_ok() {
             Some(Guard(self))
         } else {
             None
@@ -165,48 +388,205 @@ impl<T> ThreadCell<T> {
     }
 
     /// Acquires a `ThreadCell` returning a `GuardMut` that releases it when becoming dropped.
+    /// The guard is exclusive, while it exists `get()` and friends refuse to access the
+    /// cell, thus this needs only a shared reference and works on plain statics. References
+    /// from `get()` or `get_shared()` may outlive any guard, once they were handed out the
+    /// value is shared and this is refused.
     ///
     /// # Panics
     ///
-    /// When the cell is owned by another thread.
+    /// When the cell is owned by another thread or its value is pinned or shared.
     #[inline]
-    pub fn acquire_guard_mut(&mut self) -> GuardMut<T> {
-        self.thread_id
-            .compare_exchange(
-                0,
-                current_thread_id() | GUARD_BIT,
-                Ordering::Acquire,
-                Ordering::Relaxed,
-            )
-            .expect("Thread can not acquire ThreadCell");
-        GuardMut(self)
+    #[track_caller]
+    pub fn acquire_guard_mut(&self) -> GuardMut<'_, T, I> {
+        if let Err(owner) = self.take_ordered(I::current().get() | GUARD_BIT | EXCLUSIVE_BIT) {
+            self.acquire_failed(owner);
+        }
+        self.unpinned_guard_mut()
     }
 
     /// Acquires a `ThreadCell` returning a `Option<GuardMut>` that releases it when becoming
     /// dropped.  Returns `None` when self is owned by another thread.
+    ///
+    /// # Panics
+    ///
+    /// When the value of the cell is pinned or shared.
     #[inline]
-    pub fn try_acquire_guard_mut(&mut self) -> Option<GuardMut<T>> {
+    #[track_caller]
+    pub fn try_acquire_guard_mut(&self) -> Option<GuardMut<'_, T, I>> {
         if self
-            .thread_id
-            .compare_exchange(
-                0,
-                current_thread_id() | GUARD_BIT,
-                Ordering::Acquire,
-                Ordering::Relaxed,
-            )
+            .try_take(I::current().get() | GUARD_BIT | EXCLUSIVE_BIT)
             .is_ok()
         {
-            Some(GuardMut(self))
+            Some(self.unpinned_guard_mut())
         } else {
             None
         }
     }
 
+    /// Wraps the exclusively taken cell in a `GuardMut`. Its `DerefMut` hands out unpinned
+    /// references, thus this is refused once the value was accessed pinned or shared.
+    #[inline]
+    #[track_caller]
+    fn unpinned_guard_mut(&self) -> GuardMut<'_, T, I> {
+        // constructed first to release the cell when panicking
+        let guard = GuardMut(self);
+        let word = self.thread_id.load(Ordering::Relaxed);
+        assert!(word & PINNED_BIT == 0, "ThreadCell value is pinned");
+        assert!(word & SHARED_BIT == 0, "ThreadCell value is shared");
+        guard
+    }
+
+    /// Acquires a pinned `ThreadCell` returning a pinned `GuardMut` that releases it when
+    /// becoming dropped. The guard gives access to the value as `Pin<&mut T>`, which allows
+    /// storing `!Unpin` values like futures in cells and polling them from whatever thread
+    /// currently owns the cell.
+    ///
+    /// Pinning is structural: the value is pinned as long as the cell is. Once accessed
+    /// pinned the cell refuses `acquire_guard_mut()`, `with_mut()` and their `try_*`
+    /// variants for good, since these hand out `&mut T` through a shared reference. Methods
+    /// taking `&mut self` or `self` are not reachable from a pinned cell unless `T: Unpin`.
+    /// Deferred drop cells can not move a pinned value to its owner, dropping them from
+    /// another thread panics like for any other cell.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is owned by another thread or its value is shared.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn acquire_guard_pinned(self: Pin<&Self>) -> Pin<GuardMut<'_, T, I>> {
+        let this = self.get_ref();
+        if let Err(owner) = this.take_ordered(I::current().get() | GUARD_BIT | EXCLUSIVE_BIT) {
+            this.acquire_failed(owner);
+        }
+        this.pinned_guard_mut()
+    }
+
+    /// Acquires a pinned `ThreadCell` returning a `Option<Pin<GuardMut>>` that releases it
+    /// when becoming dropped.  Returns `None` when self is owned by another thread. See
+    /// `acquire_guard_pinned()`.
+    ///
+    /// # Panics
+    ///
+    /// When the value of the cell is shared.
+    #[inline]
+    #[must_use]
+    #[track_caller]
+    pub fn try_acquire_guard_pinned(self: Pin<&Self>) -> Option<Pin<GuardMut<'_, T, I>>> {
+        let this = self.get_ref();
+        if this
+            .try_take(I::current().get() | GUARD_BIT | EXCLUSIVE_BIT)
+            .is_ok()
+        {
+            Some(this.pinned_guard_mut())
+        } else {
+            None
+        }
+    }
+
+    /// Marks the value of the exclusively taken cell as pinned and wraps it in a pinned
+    /// `GuardMut`.
+    #[inline]
+    #[track_caller]
+    fn pinned_guard_mut(&self) -> Pin<GuardMut<'_, T, I>> {
+        // constructed first to release the cell when panicking
+        let guard = GuardMut(self);
+        assert!(
+            self.thread_id.load(Ordering::Relaxed) & SHARED_BIT == 0,
+            "ThreadCell value is shared"
+        );
+        self.thread_id.store(
+            I::current().get() | GUARD_BIT | EXCLUSIVE_BIT | PINNED_BIT,
+            Ordering::Relaxed,
+        );
+        // SAFETY: the cell is pinned and the flag keeps any unpinned `&mut T` from being
+        // handed out again, thus the value is never moved until it is dropped in place
+        unsafe { Pin::new_unchecked(guard) }
+    }
+
+    /// Takes the ownership of a cell, waiting until it becomes disowned when it is owned by
+    /// another thread. Waiting is done by polling with a backoff, `ThreadCells` have no
+    /// means to notify waiting threads.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Deadlock` error when waiting would never finish because the owner is
+    /// (transitively) waiting for a cell this thread owns. This includes the case when this
+    /// thread already owns the cell.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn acquire_wait(&self) -> Result<(), Deadlock> {
+        self.wait_take(I::current().get(), None).map(|_| ())
+    }
+
+    /// Tries to take the ownership of a cell, waiting at most `timeout` for it to become
+    /// disowned. Returns `Ok(true)` when the ownership could be obtained and `Ok(false)` on
+    /// timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Deadlock` error when waiting would never finish, see `acquire_wait()`.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn try_acquire_wait(&self, timeout: Duration) -> Result<bool, Deadlock> {
+        self.wait_take(I::current().get(), Some(Instant::now() + timeout))
+    }
+
+    /// Acquires a `ThreadCell` returning a `Guard`, waiting until it becomes disowned when
+    /// it is owned by another thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Deadlock` error when waiting would never finish, see `acquire_wait()`.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn acquire_guard_wait(&self) -> Result<Guard<'_, T, I>, Deadlock> {
+        self.wait_take(I::current().get() | GUARD_BIT, None)?;
+        Ok(Guard(self))
+    }
+
+    /// Polls the cell until it can be taken, the deadline is reached or a deadlock is
+    /// detected.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    fn wait_take(&self, owner: u64, deadline: Option<Instant>) -> Result<bool, Deadlock> {
+        let waiter = owner & !FLAGS;
+        diagnostics::check_rank(self.rank());
+        let mut backoff = wait::Backoff::new();
+        let result = loop {
+            let holder = match self.try_take(owner) {
+                Ok(()) => break Ok(true),
+                Err(FROZEN) => {
+                    wait::done(TypeId::of::<I>(), waiter);
+                    self.acquire_failed(FROZEN)
+                }
+                // readers are not tracked for deadlock detection
+                Err(holder) if holder & READERS_BIT != 0 => 0,
+                Err(holder) => holder & !FLAGS,
+            };
+            if holder != 0 {
+                wait::waiting(TypeId::of::<I>(), waiter, holder)?;
+            }
+            let limit = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(limit) => Some(limit),
+                    None => break Ok(false),
+                },
+                None => None,
+            };
+            backoff.snooze(limit);
+        };
+        wait::done(TypeId::of::<I>(), waiter);
+        result
+    }
+
     /// Runs a closure on a `ThreadCell` with acquire/release.
     ///
     /// # Panics
     ///
     /// When the cell is already owned by the current thread or is owned by another thread.
+    #[track_caller]
     pub fn with<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
         f(&*self.acquire_guard())
     }
@@ -215,13 +595,16 @@ impl<T> ThreadCell<T> {
     ///
     /// # Panics
     ///
-    /// When the cell is already owned by the current thread or is owned by another thread.
-    pub fn with_mut<R, F: FnOnce(&mut T) -> R>(&mut self, f: F) -> R {
+    /// When the cell is already owned by the current thread, is owned by another thread or
+    /// its value is pinned or shared.
+    #[track_caller]
+    pub fn with_mut<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
         f(&mut *self.acquire_guard_mut())
     }
 
     /// Tries to run a closure on a `ThreadCell` with acquire/release.  Returns `Some(Result)`
     /// when the cell could be acquired and None when it is owned by another thread.
+    #[track_caller]
     pub fn try_with<R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
         Some(f(&*self.try_acquire_guard()?))
     }
@@ -229,10 +612,39 @@ impl<T> ThreadCell<T> {
     /// Tries to run a closure on a mutable `ThreadCell` with acquire/release.  Returns
     /// `Some(Result)` when the cell could be acquired and None when it is owned by another
     /// thread.
-    pub fn try_with_mut<R, F: FnOnce(&mut T) -> R>(&mut self, f: F) -> Option<R> {
+    ///
+    /// # Panics
+    ///
+    /// When the value of the cell is pinned or shared.
+    #[track_caller]
+    pub fn try_with_mut<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
         Some(f(&mut *self.try_acquire_guard_mut()?))
     }
 
+    /// Runs a closure on the pinned value of a pinned `ThreadCell` with acquire/release. See
+    /// `acquire_guard_pinned()`.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is already owned by the current thread, is owned by another thread or
+    /// its value is shared.
+    #[track_caller]
+    pub fn with_pinned<R, F: FnOnce(Pin<&mut T>) -> R>(self: Pin<&Self>, f: F) -> R {
+        f(self.acquire_guard_pinned().as_mut())
+    }
+
+    /// Tries to run a closure on the pinned value of a pinned `ThreadCell` with
+    /// acquire/release.  Returns `Some(Result)` when the cell could be acquired and None
+    /// when it is owned by another thread.
+    ///
+    /// # Panics
+    ///
+    /// When the value of the cell is shared.
+    #[track_caller]
+    pub fn try_with_pinned<R, F: FnOnce(Pin<&mut T>) -> R>(self: Pin<&Self>, f: F) -> Option<R> {
+        Some(f(self.try_acquire_guard_pinned()?.as_mut()))
+    }
+
     /// Takes the ownership of a cell unconditionally. This is a no-op when the cell is
     /// already owned by the current thread. Returns 'self' thus it can be chained with
     /// `.release()`.
@@ -250,13 +662,17 @@ impl<T> ThreadCell<T> {
     ///
     /// The `ThreadCell` has a `Guard` on it. `steal()` can only be used with acquire/release
     /// semantics.
+    #[track_caller]
     pub unsafe fn steal(&self) -> &Self {
         if !self.is_acquired() {
+            let word = self.thread_id.load(Ordering::Acquire);
             assert!(
-                self.thread_id.load(Ordering::Acquire) & GUARD_BIT == 0,
+                word & (GUARD_BIT | READERS_BIT) == 0,
                 "Can't steal guarded ThreadCell"
             );
-            self.thread_id.store(current_thread_id(), Ordering::SeqCst);
+            self.thread_id
+                .store(I::current().get() | word & STICKY, Ordering::SeqCst);
+            diagnostics::record(self.addr(), I::current().get(), self.rank());
         }
 
         self
@@ -272,34 +688,94 @@ impl<T> ThreadCell<T> {
     ///
     /// The current thread does not own the cell.
     pub unsafe fn release(&self) {
+        let sticky = self.thread_id.load(Ordering::Relaxed) & STICKY;
         self.thread_id
-            .compare_exchange(current_thread_id(), 0, Ordering::Release, Ordering::Relaxed)
+            .compare_exchange(
+                I::current().get() | sticky,
+                sticky,
+                Ordering::Release,
+                Ordering::Relaxed,
+            )
             .expect("Thread has no access to ThreadCell");
+        diagnostics::forget(self.addr(), I::current().get());
+        #[cfg(feature = "std")]
+        deferred::released::<I>();
     }
 
     /// Unsafe as it doesn't check for ownership.
     #[mutants::skip]
     unsafe fn release_unchecked(&self) {
-        debug_assert!(self.is_owned());
-        self.thread_id.store(0, Ordering::Release);
+        debug_assert!(self.thread_id.load(Ordering::Relaxed) & !FLAGS == I::current().get());
+        diagnostics::forget(self.addr(), I::current().get());
+        let sticky = self.thread_id.load(Ordering::Relaxed) & STICKY;
+        self.thread_id.store(sticky, Ordering::Release);
+        #[cfg(feature = "std")]
+        deferred::released::<I>();
     }
 
     /// Tries to set a `ThreadCell` which is owned by the current thread into the disowned
     /// state. Returns *true* on success and *false* when the current thread does not own the
     /// cell.
     pub fn try_release(&self) -> bool {
+        let sticky = self.thread_id.load(Ordering::Relaxed) & STICKY;
+        let released = self
+            .thread_id
+            .compare_exchange(
+                I::current().get() | sticky,
+                sticky,
+                Ordering::Release,
+                Ordering::Relaxed,
+            )
+            .is_ok();
+        if released {
+            diagnostics::forget(self.addr(), I::current().get());
+        }
+        #[cfg(feature = "std")]
+        deferred::released::<I>();
+        released
+    }
+
+    /// Takes a cell in transit, setting its ownership word to the current thread with
+    /// `flags`. Spawning and joining the thread synchronizes with the previous owner.
+    ///
+    /// # Panics
+    ///
+    /// The cell is not in transit.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    fn receive(&self, flags: u64) {
         self.thread_id
-            .compare_exchange(current_thread_id(), 0, Ordering::Release, Ordering::Relaxed)
-            .is_ok()
+            .compare_exchange(
+                TRANSIT,
+                I::current().get() | flags,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .expect("ThreadCell is not in transit");
+        diagnostics::record(self.addr(), I::current().get(), self.rank());
     }
 
-    /// Returns true when the current thread owns this cell.
+    /// Puts a cell that is owned by the current thread in transit to a thread that is about to
+    /// be spawned or to the joining thread.
+    ///
+    /// # Safety
+    ///
+    /// The current thread must own the cell and not use any references to it afterwards.
+    #[cfg(feature = "std")]
+    unsafe fn send_off(&self) {
+        diagnostics::forget(self.addr(), I::current().get());
+        self.thread_id.store(TRANSIT, Ordering::Release);
+        deferred::released::<I>();
+    }
+
+    /// Returns true when the current thread owns this cell and can access it with `get()`,
+    /// this is false while the thread holds a `GuardMut` on it.
     #[inline(always)]
     pub fn is_owned(&self) -> bool {
         // This can be Relaxed because when we already own it (with Acquire), no other thread
         // can change the ownership.  When we do not own it this may return Zero or some other
         // thread id in a racy way, which is ok (to indicate disowned state) either way.
-        self.thread_id.load(Ordering::Relaxed) & !GUARD_BIT == current_thread_id()
+        self.thread_id.load(Ordering::Relaxed) & !(GUARD_BIT | STICKY) == I::current().get()
     }
 
     /// Returns true when this `ThreadCell` is not owned by any thread. As this can change at
@@ -308,7 +784,7 @@ impl<T> ThreadCell<T> {
     /// `ThreadCell` is synchronized by some other means.
     #[inline(always)]
     pub fn is_disowned(&self) -> bool {
-        self.thread_id.load(Ordering::Acquire) == 0
+        self.thread_id.load(Ordering::Acquire) & !STICKY == 0
     }
 
     /// Returns true when the current thread owns this cell by acquire.
@@ -317,7 +793,7 @@ impl<T> ThreadCell<T> {
         // This can be Relaxed because when we already own it (with Acquire), no other thread
         // can change the ownership.  When we do not own it this may return Zero or some other
         // thread id in a racy way, which is ok (to indicate disowned state) either way.
-        self.thread_id.load(Ordering::Relaxed) == current_thread_id()
+        self.thread_id.load(Ordering::Relaxed) & !STICKY == I::current().get()
     }
 
     /// Returns true when the current thread holds a guard on this cell.
@@ -326,7 +802,24 @@ impl<T> ThreadCell<T> {
         // This can be Relaxed because when we already own it (with Acquire), no other thread
         // can change the ownership.  When we do not own it this may return Zero or some other
         // thread id in a racy way, which is ok (to indicate disowned state) either way.
-        self.thread_id.load(Ordering::Relaxed) == current_thread_id() | GUARD_BIT
+        self.thread_id.load(Ordering::Relaxed) & !(EXCLUSIVE_BIT | STICKY)
+            == I::current().get() | GUARD_BIT
+    }
+
+    /// Returns true when the current thread holds a `GuardMut` on this cell.
+    #[inline(always)]
+    pub fn is_exclusive(&self) -> bool {
+        // This can be Relaxed because when we already own it (with Acquire), no other thread
+        // can change the ownership.  When we do not own it this may return Zero or some other
+        // thread id in a racy way, which is ok (to indicate disowned state) either way.
+        self.thread_id.load(Ordering::Relaxed) & !STICKY
+            == I::current().get() | GUARD_BIT | EXCLUSIVE_BIT
+    }
+
+    #[inline]
+    #[track_caller]
+    fn assert_exclusive(&self) {
+        assert!(self.is_exclusive(), "Thread has no access to ThreadCell");
     }
 
     #[inline]
@@ -335,26 +828,42 @@ impl<T> ThreadCell<T> {
         assert!(self.is_owned(), "Thread has no access to ThreadCell");
     }
 
+    #[cold]
+    #[track_caller]
+    fn assert_frozen(&self) {
+        assert!(self.is_frozen(), "Thread has no access to ThreadCell");
+    }
+
     /// Consumes a owned cell and returns its content.
     ///
     /// # Panics
     ///
     /// The current thread does not own the cell.
     #[inline]
-    pub fn into_inner(mut self) -> T {
+    pub fn into_inner(self) -> T
+    where
+        T: Sized,
+    {
         self.assert_owned();
-        unsafe { ManuallyDrop::take(&mut self.data) }
+        diagnostics::forget(self.addr(), I::current().get());
+        // the content is moved out, thus the cell must not be dropped
+        let mut this = ManuallyDrop::new(self);
+        unsafe { ManuallyDrop::take(this.data.get_mut()) }
     }
 
-    /// Gets an immutable reference to the cells content.
+    /// Gets an immutable reference to the cells content. Any thread can do so once the cell
+    /// is frozen. The reference may outlive the ownership, thus the value becomes shared and
+    /// is not handed out by exclusive guards anymore, see `acquire_guard_mut()`.
     ///
     /// # Panics
     ///
     /// The current thread does not own the cell.
     #[inline]
     pub fn get(&self) -> &T {
-        self.assert_owned();
-        &self.data
+        if !self.is_owned() {
+            self.assert_frozen();
+        }
+        self.share()
     }
 
     /// Gets a mutable reference to the cells content.
@@ -365,15 +874,15 @@ impl<T> ThreadCell<T> {
     #[inline]
     pub fn get_mut(&mut self) -> &mut T {
         self.assert_owned();
-        &mut self.data
+        self.data.get_mut()
     }
 
     /// Tries to get an immutable reference to the cells content.
-    /// Returns 'None' when the thread does not own the cell.
+    /// Returns 'None' when the thread does not own the cell and it is not frozen.
     #[inline]
     pub fn try_get(&self) -> Option<&T> {
-        if self.is_owned() {
-            Some(&self.data)
+        if self.is_owned() || self.is_frozen() {
+            Some(self.share())
         } else {
             None
         }
@@ -384,24 +893,182 @@ impl<T> ThreadCell<T> {
     #[inline]
     pub fn try_get_mut(&mut self) -> Option<&mut T> {
         if self.is_owned() {
-            Some(&mut self.data)
+            Some(self.data.get_mut())
         } else {
             None
         }
     }
 
+    /// Runs `f` with exclusive access to the value. A cell acquired by the current thread is
+    /// marked exclusive for the time of the call, no references from `get()` exist unless the
+    /// value is shared. Any other cell is acquired through a `GuardMut`.
+    #[track_caller]
+    fn with_exclusive<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        if !self.is_acquired() {
+            return f(&mut self.acquire_guard_mut());
+        }
+        let word = self.thread_id.load(Ordering::Relaxed);
+        assert!(word & PINNED_BIT == 0, "ThreadCell value is pinned");
+        assert!(word & SHARED_BIT == 0, "ThreadCell value is shared");
+        self.thread_id
+            .store(word | GUARD_BIT | EXCLUSIVE_BIT, Ordering::Relaxed);
+        // restores the plain ownership when `f` panics as well
+        let _exclusive = Exclusive(self);
+        // SAFETY: the flags keep the value from being accessed by other means meanwhile
+        f(unsafe { &mut *self.data.get() })
+    }
+
+    /// Replaces the content of a cell, returning the old value. A cell owned by the current
+    /// thread is accessed in place, any other is acquired through a `GuardMut`. Like
+    /// `with_mut()` this needs only a shared reference and works on plain statics.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is owned by another thread, guarded by the current one or its value is
+    /// pinned or shared.
+    #[inline]
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T
+    where
+        T: Sized,
+    {
+        self.with_exclusive(|old| mem::replace(old, value))
+    }
+
+    /// Takes the content of a cell, leaving `Default::default()` in its place. Accesses the
+    /// cell like `replace()`.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is owned by another thread, guarded by the current one or its value is
+    /// pinned or shared.
+    #[inline]
+    #[track_caller]
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.with_exclusive(mem::take)
+    }
+
+    /// Sets the content of a cell, dropping the old value. Accesses the cell like
+    /// `replace()`.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is owned by another thread, guarded by the current one or its value is
+    /// pinned or shared.
+    #[inline]
+    #[track_caller]
+    pub fn set(&self, value: T)
+    where
+        T: Sized,
+    {
+        self.with_exclusive(|old| *old = value);
+    }
+
+    /// Updates the content of a cell with the value computed from the old one. Accesses the
+    /// cell like `replace()`.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is owned by another thread, guarded by the current one or its value is
+    /// pinned or shared.
+    #[inline]
+    #[track_caller]
+    pub fn update<F: FnOnce(&T) -> T>(&self, f: F)
+    where
+        T: Sized,
+    {
+        self.with_exclusive(|value| *value = f(value));
+    }
+
+    /// Swaps the contents of two cells. Each cell is accessed like by `replace()`. Swapping a
+    /// cell with itself does nothing.
+    ///
+    /// # Panics
+    ///
+    /// When either cell is owned by another thread, guarded by the current one or its value
+    /// is pinned or shared.
+    #[track_caller]
+    pub fn swap(&self, other: &Self)
+    where
+        T: Sized,
+    {
+        if ptr::eq(self, other) {
+            return;
+        }
+        self.with_exclusive(|this| other.with_exclusive(|other| mem::swap(this, other)));
+    }
+
+    /// Consumes a cell and maps its content to a new cell. The new cell keeps the ownership
+    /// state, a owned cell stays owned by the current thread and a disowned cell stays
+    /// disowned. The new cell of a frozen one is disowned.
+    ///
+    /// # Panics
+    ///
+    /// Another thread owns the cell.
+    #[track_caller]
+    pub fn map_into<U, F: FnOnce(T) -> U>(self, f: F) -> OwnerCell<U, I>
+    where
+        T: Sized,
+    {
+        let mut word = self.thread_id.load(Ordering::Acquire);
+        let owner = word & !FLAGS;
+        assert!(
+            owner == 0 || owner == I::current().get(),
+            "Thread has no access to ThreadCell"
+        );
+        diagnostics::forget(self.addr(), owner);
+        // the new value may not be `Sync`
+        if word == FROZEN {
+            word = 0;
+        }
+        let mut this = ManuallyDrop::new(self);
+        let data = unsafe { ManuallyDrop::take(this.data.get_mut()) };
+        OwnerCell {
+            data: UnsafeCell::new(ManuallyDrop::new(f(data))),
+            // the new value was never pinned or shared
+            thread_id: I::Word::new(word & !STICKY),
+            #[cfg(debug_assertions)]
+            rank: this.rank,
+            identity: PhantomData,
+        }
+    }
+
+    /// Hands out a reference that lives as long as the cell. The cell may be released
+    /// safely by a guard while the reference still exists, thus the value is marked shared
+    /// and no exclusive guard is handed out through a shared reference anymore.
+    #[inline]
+    fn share(&self) -> &T {
+        let mut word = self.thread_id.load(Ordering::Relaxed);
+        while word & SHARED_BIT == 0 {
+            match self.thread_id.compare_exchange(
+                word,
+                word | SHARED_BIT,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(current) => word = current,
+            }
+        }
+        // SAFETY: the caller checked that the current thread owns the cell or it is frozen
+        unsafe { &*self.data.get() }
+    }
+
     /// Gets an immutable reference to the cells content without checking for ownership.
     ///
     /// # Safety
     ///
     /// This is always safe when the thread owns the cell, for example after a `acquire()`
     /// call.  When the current thread does not own the cell then it is only safe when T is a
-    /// Sync type.
+    /// Sync type and no `GuardMut` exists meanwhile, `get_shared()` ensures this.
     // PLANNED: When specialization is available: 'fn is_sync<T>() -> bool' and debug_assert!(is_owned() || is_sync::<T>())
     #[inline]
     pub unsafe fn get_unchecked(&self) -> &T {
         debug_assert!(self.is_owned(), "Thread has no access to ThreadCell");
-        &self.data
+        &*self.data.get()
     }
 
     /// Gets an mutable reference to the cells content without checking for ownership.
@@ -414,7 +1081,7 @@ impl<T> ThreadCell<T> {
     // PLANNED: When specialization is available: 'fn is_sync<T>() -> bool' and debug_assert!(is_owned() || is_sync::<T>())
     #[inline]
     pub unsafe fn get_mut_unchecked(&mut self) -> &mut T {
-        &mut self.data
+        self.data.get_mut()
     }
 }
 
@@ -424,15 +1091,16 @@ impl<T> ThreadCell<T> {
 ///
 /// Another thread owns the cell.
 #[mutants::skip]
-impl<T> Drop for ThreadCell<T> {
+impl<T: ?Sized, I: OwnerIdentity> Drop for OwnerCell<T, I> {
     // In debug builds we check first for ownership since dropping cells whose types do not
     // need dropping would still be a violation.
     #[cfg(debug_assertions)]
     fn drop(&mut self) {
-        let owner = self.thread_id.load(Ordering::Acquire) & !GUARD_BIT;
-        if owner == 0 || owner == current_thread_id() {
+        let owner = self.thread_id.load(Ordering::Acquire) & !FLAGS;
+        if owner == 0 || owner == I::current().get() {
+            diagnostics::forget(self.addr(), owner);
             if mem::needs_drop::<T>() {
-                unsafe { ManuallyDrop::drop(&mut self.data) };
+                unsafe { ManuallyDrop::drop(self.data.get_mut()) };
             }
         } else {
             panic!("Thread has no access to ThreadCell");
@@ -445,9 +1113,9 @@ impl<T> Drop for ThreadCell<T> {
     #[cfg(not(debug_assertions))]
     fn drop(&mut self) {
         if mem::needs_drop::<T>() {
-            let owner = self.thread_id.load(Ordering::Acquire) & !GUARD_BIT;
-            if owner == 0 || owner == current_thread_id() {
-                unsafe { ManuallyDrop::drop(&mut self.data) };
+            let owner = self.thread_id.load(Ordering::Acquire) & !FLAGS;
+            if owner == 0 || owner == I::current().get() {
+                unsafe { ManuallyDrop::drop(self.data.get_mut()) };
             } else {
                 panic!("Thread has no access to ThreadCell");
             }
@@ -456,10 +1124,10 @@ impl<T> Drop for ThreadCell<T> {
 }
 
 /// Creates a new owned `ThreadCell` from the given value.
-impl<T> From<T> for ThreadCell<T> {
+impl<T, I: OwnerIdentity> From<T> for OwnerCell<T, I> {
     #[inline]
-    fn from(t: T) -> ThreadCell<T> {
-        ThreadCell::new_owned(t)
+    fn from(t: T) -> OwnerCell<T, I> {
+        OwnerCell::new_owned(t)
     }
 }
 
@@ -468,18 +1136,18 @@ impl<T> From<T> for ThreadCell<T> {
 /// # Panics
 ///
 /// Another thread owns the cell.
-impl<T: Clone> Clone for ThreadCell<T> {
+impl<T: Clone, I: OwnerIdentity> Clone for OwnerCell<T, I> {
     #[inline]
-    fn clone(&self) -> ThreadCell<T> {
-        ThreadCell::new_owned(self.get().clone())
+    fn clone(&self) -> OwnerCell<T, I> {
+        OwnerCell::new_owned(self.get().clone())
     }
 }
 
 /// Creates a new owned `ThreadCell` with the default constructed target value.
-impl<T: Default> Default for ThreadCell<T> {
+impl<T: Default, I: OwnerIdentity> Default for OwnerCell<T, I> {
     #[inline]
-    fn default() -> ThreadCell<T> {
-        ThreadCell::new_owned(T::default())
+    fn default() -> OwnerCell<T, I> {
+        OwnerCell::new_owned(T::default())
     }
 }
 
@@ -489,14 +1157,14 @@ impl<T: Default> Default for ThreadCell<T> {
 ///
 /// Either cell is not owned by the current thread.
 #[mutants::skip]
-impl<T: PartialEq> PartialEq for ThreadCell<T> {
+impl<T: ?Sized + PartialEq, I: OwnerIdentity> PartialEq for OwnerCell<T, I> {
     #[inline]
-    fn eq(&self, other: &ThreadCell<T>) -> bool {
+    fn eq(&self, other: &OwnerCell<T, I>) -> bool {
         *self.get() == *other.get()
     }
 }
 
-impl<T: Eq> Eq for ThreadCell<T> {}
+impl<T: ?Sized + Eq, I: OwnerIdentity> Eq for OwnerCell<T, I> {}
 
 /// Comparison functions between `ThreadCells`.
 ///
@@ -504,29 +1172,29 @@ impl<T: Eq> Eq for ThreadCell<T> {}
 ///
 /// Either cell is not owned by the current thread.
 #[mutants::skip]
-impl<T: PartialOrd> PartialOrd for ThreadCell<T> {
+impl<T: ?Sized + PartialOrd, I: OwnerIdentity> PartialOrd for OwnerCell<T, I> {
     #[inline]
-    fn partial_cmp(&self, other: &ThreadCell<T>) -> Option<cmp::Ordering> {
+    fn partial_cmp(&self, other: &OwnerCell<T, I>) -> Option<cmp::Ordering> {
         self.get().partial_cmp(other.get())
     }
 
     #[inline]
-    fn lt(&self, other: &ThreadCell<T>) -> bool {
+    fn lt(&self, other: &OwnerCell<T, I>) -> bool {
         *self.get() < *other.get()
     }
 
     #[inline]
-    fn le(&self, other: &ThreadCell<T>) -> bool {
+    fn le(&self, other: &OwnerCell<T, I>) -> bool {
         *self.get() <= *other.get()
     }
 
     #[inline]
-    fn gt(&self, other: &ThreadCell<T>) -> bool {
+    fn gt(&self, other: &OwnerCell<T, I>) -> bool {
         *self.get() > *other.get()
     }
 
     #[inline]
-    fn ge(&self, other: &ThreadCell<T>) -> bool {
+    fn ge(&self, other: &OwnerCell<T, I>) -> bool {
         *self.get() >= *other.get()
     }
 }
@@ -537,9 +1205,9 @@ impl<T: PartialOrd> PartialOrd for ThreadCell<T> {
 ///
 /// Either cell is not owned by the current thread.
 #[mutants::skip]
-impl<T: Ord> Ord for ThreadCell<T> {
+impl<T: ?Sized + Ord, I: OwnerIdentity> Ord for OwnerCell<T, I> {
     #[inline]
-    fn cmp(&self, other: &ThreadCell<T>) -> cmp::Ordering {
+    fn cmp(&self, other: &OwnerCell<T, I>) -> cmp::Ordering {
         self.get().cmp(other.get())
     }
 }
@@ -550,7 +1218,7 @@ impl<T: Ord> Ord for ThreadCell<T> {
 ///
 /// The cell is not owned by the current thread.
 #[mutants::skip]
-impl<T: fmt::Display> fmt::Display for ThreadCell<T> {
+impl<T: ?Sized + fmt::Display, I: OwnerIdentity> fmt::Display for OwnerCell<T, I> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         fmt::Display::fmt(self.get(), f)
     }
@@ -560,84 +1228,34 @@ impl<T: fmt::Display> fmt::Display for ThreadCell<T> {
 #[mutants::skip]
 /// Debug information of a `ThreadCell`.
 /// Prints "\<ThreadCell\>" when the current thread does not own the cell.
-impl<T: fmt::Debug> fmt::Debug for ThreadCell<T> {
+impl<T: ?Sized + fmt::Debug, I: OwnerIdentity> fmt::Debug for OwnerCell<T, I> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self.try_get() {
-            Some(data) => f.debug_struct("ThreadCell").field("data", data).finish(),
+            Some(data) => f.debug_struct("ThreadCell").field("data", &data).finish(),
             None => f.write_str("<ThreadCell>"),
         }
     }
 }
 
-#[cfg(not(feature = "nightly_thread_id_value"))]
-use std::num::NonZeroU64;
-
-/// A unique identifier for every thread.
-#[cfg(not(feature = "nightly_thread_id_value"))]
-struct ThreadId(NonZeroU64);
-
-#[cfg(not(feature = "nightly_thread_id_value"))]
-impl ThreadId {
-    #[inline]
-    #[must_use]
-    #[mutants::skip]
-    fn current() -> ThreadId {
-        thread_local!(static THREAD_ID: NonZeroU64 = {
-            static COUNTER: AtomicU64 = AtomicU64::new(1);
-            {
-                let id = NonZeroU64::new(COUNTER.fetch_add(1, Ordering::Relaxed)).unwrap();
-                assert!(id.get() <= i64::MAX as u64, "more than i64::MAX threads");
-                id
-            }
-        });
-        THREAD_ID.with(|&x| ThreadId(x))
-    }
+/// Restores the plain ownership of a cell `with_exclusive()` marked exclusive.
+struct Exclusive<'a, T: ?Sized, I: OwnerIdentity>(&'a OwnerCell<T, I>);
 
-    #[inline(always)]
-    #[must_use]
-    #[mutants::skip]
-    fn as_u64(&self) -> NonZeroU64 {
+impl<T: ?Sized, I: OwnerIdentity> Drop for Exclusive<'_, T, I> {
+    fn drop(&mut self) {
         self.0
+            .thread_id
+            .store(I::current().get(), Ordering::Relaxed);
     }
 }
 
-#[test]
-#[cfg(not(feature = "nightly_thread_id_value"))]
-fn threadid() {
-    let main = ThreadId::current().as_u64().get();
-    let child = std::thread::spawn(|| ThreadId::current().as_u64().get())
-        .join()
-        .unwrap();
-
-    // just info, actual values are unspecified
-    println!("{main}, {child}");
-
-    assert_ne!(main, 0);
-    assert_ne!(main, child);
-}
-
-#[cfg(not(feature = "nightly_thread_id_value"))]
-#[mutants::skip]
-#[inline]
-fn current_thread_id() -> u64 {
-    ThreadId::current().as_u64().get()
-}
-
-#[cfg(feature = "nightly_thread_id_value")]
-#[mutants::skip]
-#[inline]
-fn current_thread_id() -> u64 {
-    std::thread::current().id().as_u64().get()
-}
-
 /// Guards that a referenced `ThreadCell` becomes properly released when its guard becomes
 /// dropped. This covers releasing threadcells on panic.  Guards do not prevent the explicit
 /// release of a `ThreadCell`. Deref a `Guard` referencing a released `ThreadCell` will panic!
 #[repr(transparent)]
-pub struct Guard<'a, T>(&'a ThreadCell<T>);
+pub struct Guard<'a, T: ?Sized, I: OwnerIdentity = CurrentThread>(&'a OwnerCell<T, I>);
 
 /// Releases the referenced `ThreadCell` when it is owned by the current thread.
-impl<T> Drop for Guard<'_, T> {
+impl<T: ?Sized, I: OwnerIdentity> Drop for Guard<'_, T, I> {
     #[mutants::skip]
     fn drop(&mut self) {
         unsafe {
@@ -653,22 +1271,24 @@ impl<T> Drop for Guard<'_, T> {
 /// # Panics
 ///
 /// When the underlying `ThreadCell` is not owned by the current thread.
-impl<T> Deref for Guard<'_, T> {
+impl<T: ?Sized, I: OwnerIdentity> Deref for Guard<'_, T, I> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        self.0.get()
+        assert!(self.0.is_guarded(), "Thread has no access to ThreadCell");
+        // SAFETY: the reference is bound to the guard, which keeps the cell owned
+        unsafe { &*self.0.data.get() }
     }
 }
 
-/// Mutable Guard that ensures that a referenced `ThreadCell` becomes properly released when
-/// it becomes dropped.  Guards do not prevent the explicit release of a `ThreadCell`. Deref a
-/// `GuardMut` referencing a released `ThreadCell` will panic!
+/// Exclusive Guard that ensures that a referenced `ThreadCell` becomes properly released
+/// when it becomes dropped.  While a `GuardMut` exists the cell can only be accessed through
+/// it, `get()` and the like refuse access and the cell can not be released explicitly.
 #[repr(transparent)]
-pub struct GuardMut<'a, T>(&'a mut ThreadCell<T>);
+pub struct GuardMut<'a, T: ?Sized, I: OwnerIdentity = CurrentThread>(&'a OwnerCell<T, I>);
 
 /// Releases the referenced `ThreadCell` when it is owned by the current thread.
-impl<T> Drop for GuardMut<'_, T> {
+impl<T: ?Sized, I: OwnerIdentity> Drop for GuardMut<'_, T, I> {
     fn drop(&mut self) {
         unsafe {
             // SAFETY: a guard is guaranteed to own the cell
@@ -678,21 +1298,26 @@ impl<T> Drop for GuardMut<'_, T> {
 }
 
 /// One can deref a `GuardMut` as long the `ThreadCell` is owned by the current thread this
-/// should be the case as long the guarded `ThreadCell` got not explicitly released or stolen.
+/// should be the case as long the guarded `ThreadCell` got not stolen.
 ///
 /// # Panics
 ///
 /// When the underlying `ThreadCell` is not owned by the current thread.
-impl<T> Deref for GuardMut<'_, T> {
+impl<T: ?Sized, I: OwnerIdentity> Deref for GuardMut<'_, T, I> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        self.0.get()
+        self.0.assert_exclusive();
+        // SAFETY: the exclusive guard is the only way to access the cell
+        unsafe { &*self.0.data.get() }
     }
 }
 
-impl<T> DerefMut for GuardMut<'_, T> {
+impl<T: ?Sized, I: OwnerIdentity> DerefMut for GuardMut<'_, T, I> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.0.get_mut()
+        self.0.assert_exclusive();
+        // SAFETY: the exclusive guard is the only way to access the cell and `&mut self`
+        // ensures there are no other references obtained from this guard
+        unsafe { &mut *self.0.data.get() }
     }
 }
@@ -0,0 +1,147 @@
+//! Running closures on the thread owning a cell.
+//!
+//! A closure posted to a cell owned by another thread is queued in the mailbox of that
+//! thread, see `deferred`. When the owner runs it but does not own the cell anymore it runs
+//! with acquire/release or is passed on to the new owner. Closures for disowned cells run right away with
+//! acquire/release, those for cells read by shared guards with another shared guard.
+
+use core::sync::atomic::Ordering;
+use std::panic;
+use std::sync::mpsc::{self, TryRecvError};
+
+use crate::{
+    deferred, wait, OwnerCell, OwnerIdentity, OwnerWord, FLAGS, READERS_BIT, STICKY, TRANSIT,
+};
+
+impl<T: ?Sized, I: OwnerIdentity> OwnerCell<T, I> {
+    /// Runs `f` on the value on the thread owning the cell. When the current thread owns the
+    /// cell or can acquire it `f` runs right away, as it does when the cell is read by shared
+    /// guards. Otherwise it is queued to the owner, which runs it the next time it releases
+    /// any cell or calls `poll_mailbox()`. When the owner does not own the cell anymore then
+    /// `f` runs with acquire/release or is passed on to the new owner. Closures posted to
+    /// threads that exit first are leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics of `f` propagate when it runs right away. Otherwise they are caught and resumed
+    /// by the release or `poll_mailbox()` call that ran it, after the remaining closures ran.
+    pub fn post<F: FnOnce(&T) + Send + 'static>(&'static self, f: F) {
+        let message = Message {
+            cell: self,
+            f,
+            done: drop,
+        };
+        // SAFETY: the cell is static and the closure owns its data
+        unsafe { message.deliver() };
+    }
+
+    /// Runs `f` on the value on the thread owning the cell and returns its result. Like
+    /// `post()`, but blocks until the owner ran `f`. The current thread runs closures posted
+    /// to it meanwhile, thus two threads calling into each others cells do not deadlock.
+    ///
+    /// # Panics
+    ///
+    /// The current thread holds a `GuardMut` on the cell, which would block forever. `f`
+    /// panicked on the owner. The first panic of closures the current thread ran meanwhile
+    /// is resumed once `f` is done.
+    #[track_caller]
+    pub fn call_on_owner<R: Send, F: FnOnce(&T) -> R + Send>(&self, f: F) -> R {
+        assert!(!self.is_exclusive(), "Thread has no access to ThreadCell");
+        let (sender, receiver) = mpsc::channel();
+        let message = Message {
+            cell: self,
+            f,
+            done: move |result| {
+                let _ = sender.send(result);
+            },
+        };
+        // SAFETY: we wait until the message is consumed, the sender is dropped last
+        unsafe { message.deliver() };
+        let mut backoff = wait::Backoff::new();
+        // the message refers to this frame, panics of other closures wait until it is done
+        let mut panicked = None;
+        let result = loop {
+            match receiver.try_recv() {
+                Ok(result) => break Ok(result),
+                Err(TryRecvError::Disconnected) => break Err(()),
+                Err(TryRecvError::Empty) => {}
+            }
+            if let Some(payload) = deferred::poll::<I>() {
+                panicked.get_or_insert(payload);
+            }
+            backoff.snooze(None);
+        };
+        if let Some(payload) = panicked {
+            panic::resume_unwind(payload);
+        }
+        result.unwrap_or_else(|()| panic!("ThreadCell owner panicked"))
+    }
+
+    /// The owner to queue messages for, 0 when the cell is in transit and `None` when it is
+    /// disowned or read by shared guards.
+    fn mailbox_owner(&self) -> Option<u64> {
+        // pairs with the fences after releasing and receiving cells
+        let word = self.thread_id.load(Ordering::SeqCst);
+        if word & READERS_BIT != 0 {
+            return None;
+        }
+        match word & !FLAGS {
+            0 if word & !STICKY == TRANSIT => Some(0),
+            0 => None,
+            owner => Some(owner),
+        }
+    }
+}
+
+/// A closure queued for the owner of `cell`, its result is passed to `done` after the cell
+/// is not used anymore.
+struct Message<'a, T: ?Sized, I: OwnerIdentity, F, D> {
+    cell: &'a OwnerCell<T, I>,
+    f: F,
+    done: D,
+}
+
+impl<T: ?Sized, I: OwnerIdentity, R, F: FnOnce(&T) -> R, D: FnOnce(R)> Message<'_, T, I, F, D> {
+    /// Runs the closure when the current thread can access the cell, queues it for the owner
+    /// otherwise.
+    ///
+    /// # Safety
+    ///
+    /// The cell and anything the closures refer to must outlive the message.
+    unsafe fn deliver(self) {
+        let mut message = self;
+        loop {
+            let cell = message.cell;
+            // frozen cells hold `Sync` values which every thread can read
+            if cell.is_owned() || cell.is_frozen() {
+                let result = (message.f)(cell.get_unchecked());
+                return (message.done)(result);
+            }
+            if let Some(guard) = cell.try_acquire_guard() {
+                let Message { f, done, .. } = message;
+                let result = f(&guard);
+                drop(guard);
+                return done(result);
+            }
+            // readers never block, the current thread may be one of them
+            if let Some(guard) = cell.try_join_shared() {
+                let Message { f, done, .. } = message;
+                let result = f(&guard);
+                drop(guard);
+                return done(result);
+            }
+            match deferred::send::<I, _>(
+                || cell.mailbox_owner(),
+                message,
+                |message| {
+                    // SAFETY: the caller of the first delivery ensured this
+                    unsafe { message.deliver() }
+                },
+            ) {
+                Ok(()) => return,
+                // disowned meanwhile
+                Err(returned) => message = returned,
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Cells owned by the main thread.
+
+use core::cell::LazyCell;
+use std::sync::OnceLock;
+
+use crate::{CurrentThread, DeferredCell, OwnerIdentity};
+
+/// Id of the designated main thread.
+static MAIN_THREAD: OnceLock<u64> = OnceLock::new();
+
+/// Designates the current thread as the main thread. This must be called once before any
+/// `MainThreadCell` is used, usually at the start of `main()`. Calling it again from the
+/// same thread does nothing.
+///
+/// # Panics
+///
+/// Another thread is the main thread already.
+pub fn mark_main_thread() {
+    let current = CurrentThread::current().get();
+    assert!(
+        *MAIN_THREAD.get_or_init(|| current) == current,
+        "Another thread is the main thread"
+    );
+}
+
+/// Returns true when the current thread is the main thread designated by
+/// `mark_main_thread()`. Returns false when `mark_main_thread()` was not called yet, thread
+/// names are no proof, any thread can be named "main".
+#[must_use]
+pub fn is_main_thread() -> bool {
+    MAIN_THREAD
+        .get()
+        .is_some_and(|main| *main == CurrentThread::current().get())
+}
+
+/// Panics for a thread that can not access a `MainThreadCell`.
+#[cold]
+#[track_caller]
+fn not_main_thread() -> ! {
+    assert!(
+        MAIN_THREAD.get().is_some(),
+        "mark_main_thread() was not called"
+    );
+    panic!("Thread is not the main thread")
+}
+
+/// A cell owned by the main thread, for globals like GUI or event loop state that only the
+/// main thread may touch. The value is initialized on the first access, no acquire is
+/// needed. Other threads can't access it at all.
+///
+/// Like `PinnedCell` it is `Send` and `Sync` for any `T`, the value is only ever created,
+/// accessed and dropped by the main thread. When another thread drops the cell the value is
+/// queued to the main thread, see `DeferredCell`.
+pub struct MainThreadCell<T: 'static>(DeferredCell<LazyCell<T>>);
+
+// SAFETY: the value is only ever created, accessed and dropped by the main thread
+unsafe impl<T: 'static> Send for MainThreadCell<T> {}
+unsafe impl<T: 'static> Sync for MainThreadCell<T> {}
+
+impl<T: 'static> MainThreadCell<T> {
+    const_fn! {
+        /// Creates a `MainThreadCell` which calls `init` on the first access. This is a const
+        /// fn which allows static construction.
+        pub const fn new(init: fn() -> T) -> Self {
+            MainThreadCell(DeferredCell::new_disowned(LazyCell::new(init)))
+        }
+    }
+
+    /// Returns true when the current thread is the main thread and thus can access the cell.
+    /// Returns false when `mark_main_thread()` was not called.
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.0.is_owned() || is_main_thread()
+    }
+
+    /// Takes the cell on the first access by the main thread, it is never released.
+    #[inline]
+    fn claim(&self) -> bool {
+        self.0.is_owned() || (is_main_thread() && self.0.try_acquire())
+    }
+
+    /// Gets an immutable reference to the value, initializing it when necessary.
+    ///
+    /// # Panics
+    ///
+    /// The current thread is not the main thread or `mark_main_thread()` was not called.
+    #[inline]
+    #[track_caller]
+    pub fn get(&self) -> &T {
+        self.try_get().unwrap_or_else(|| not_main_thread())
+    }
+
+    /// Gets a mutable reference to the value, initializing it when necessary.
+    ///
+    /// # Panics
+    ///
+    /// The current thread is not the main thread or `mark_main_thread()` was not called.
+    #[inline]
+    #[track_caller]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.try_get_mut().unwrap_or_else(|| not_main_thread())
+    }
+
+    /// Tries to get an immutable reference to the value, initializing it when necessary.
+    /// Returns 'None' when the current thread is not the main thread or
+    /// `mark_main_thread()` was not called.
+    #[inline]
+    pub fn try_get(&self) -> Option<&T> {
+        if self.claim() {
+            // SAFETY: the main thread owns the cell for good
+            Some(LazyCell::force(unsafe { self.0.get_unchecked() }))
+        } else {
+            None
+        }
+    }
+
+    /// Tries to get a mutable reference to the value, initializing it when necessary.
+    /// Returns 'None' when the current thread is not the main thread or
+    /// `mark_main_thread()` was not called.
+    #[inline]
+    pub fn try_get_mut(&mut self) -> Option<&mut T> {
+        if self.claim() {
+            // SAFETY: the main thread owns the cell for good
+            Some(LazyCell::force_mut(unsafe { self.0.get_mut_unchecked() }))
+        } else {
+            None
+        }
+    }
+}
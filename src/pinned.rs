@@ -0,0 +1,73 @@
+//! Cells pinned to the thread that created them.
+
+use crate::DeferredCell;
+
+/// A cell that is owned by the thread which created it for its whole lifetime. Unlike
+/// `ThreadCell` it is `Send` and `Sync` for any `T`, which allows putting thread affine
+/// values like `Rc`, GUI handles or FFI contexts into shared structures.
+///
+/// Only the creating thread can access the value. When another thread drops the cell the
+/// value is queued to the creating thread and dropped there, see
+/// `DeferredCell`. When the creating thread exited the value is leaked.
+pub struct PinnedCell<T: 'static>(DeferredCell<T>);
+
+// SAFETY: the value is only ever accessed and dropped by the creating thread
+unsafe impl<T: 'static> Send for PinnedCell<T> {}
+unsafe impl<T: 'static> Sync for PinnedCell<T> {}
+
+impl<T: 'static> PinnedCell<T> {
+    /// Creates a cell pinned to the current thread.
+    pub fn new(value: T) -> Self {
+        PinnedCell(DeferredCell::new_owned(value))
+    }
+
+    /// Returns true when the current thread created the cell and thus can access it.
+    #[inline(always)]
+    pub fn is_owned(&self) -> bool {
+        self.0.is_owned()
+    }
+
+    /// Gets an immutable reference to the value.
+    ///
+    /// # Panics
+    ///
+    /// The current thread did not create the cell.
+    #[inline]
+    pub fn get(&self) -> &T {
+        self.0.get()
+    }
+
+    /// Gets a mutable reference to the value.
+    ///
+    /// # Panics
+    ///
+    /// The current thread did not create the cell.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut()
+    }
+
+    /// Tries to get an immutable reference to the value.
+    /// Returns 'None' when the current thread did not create the cell.
+    #[inline]
+    pub fn try_get(&self) -> Option<&T> {
+        self.0.try_get()
+    }
+
+    /// Tries to get a mutable reference to the value.
+    /// Returns 'None' when the current thread did not create the cell.
+    #[inline]
+    pub fn try_get_mut(&mut self) -> Option<&mut T> {
+        self.0.try_get_mut()
+    }
+
+    /// Consumes the cell and returns its value.
+    ///
+    /// # Panics
+    ///
+    /// The current thread did not create the cell.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
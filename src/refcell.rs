@@ -0,0 +1,220 @@
+//! A `ThreadCell` with built in borrow tracking.
+
+use core::cell::{Cell, UnsafeCell};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use crate::{CurrentThread, OwnerIdentity, ThreadCell, GUARD_BIT};
+
+/// A mutable memory location with dynamically checked borrow rules that can be owned by a
+/// single thread or none at all. This replaces `ThreadCell<RefCell<T>>` with a single
+/// ownership check.
+///
+/// Borrowing a disowned cell makes the current thread its owner, it gets disowned again when
+/// the last borrow is dropped. Borrowing a cell owned by another thread fails.
+pub struct ThreadRefCell<T>(ThreadCell<RefState<T>>);
+
+/// The value and its borrow counter, only accessed by the owning thread. Positive counts
+/// are shared borrows, -1 is a mutable borrow.
+struct RefState<T> {
+    borrow: Cell<isize>,
+    value: UnsafeCell<T>,
+}
+
+impl<T> ThreadRefCell<T> {
+    const_fn! {
+        /// Creates a disowned `ThreadRefCell`. This is a const fn which allows static
+        /// construction.
+        pub const fn new(value: T) -> Self {
+            ThreadRefCell(ThreadCell::new_disowned(RefState {
+                borrow: Cell::new(0),
+                value: UnsafeCell::new(value),
+            }))
+        }
+    }
+
+    /// Takes the ownership for a borrow, returns the state or the ownership word of the
+    /// thread owning the cell.
+    #[inline]
+    #[track_caller]
+    fn enter(&self) -> Result<&RefState<T>, u64> {
+        if !self.0.is_owned() {
+            self.0.try_take(CurrentThread::current().get() | GUARD_BIT)?;
+        }
+        // SAFETY: we own the cell
+        Ok(unsafe { self.0.get_unchecked() })
+    }
+
+    /// Disowns the cell when the last borrow got dropped.
+    #[inline]
+    fn leave(&self, state: &RefState<T>) {
+        if state.borrow.get() == 0 {
+            // SAFETY: we own the cell while a borrow exists
+            unsafe { self.0.release_unchecked() };
+        }
+    }
+
+    /// Immutably borrows the value.
+    ///
+    /// # Panics
+    ///
+    /// The cell is owned by another thread or mutably borrowed.
+    #[track_caller]
+    pub fn borrow(&self) -> Ref<'_, T> {
+        match self.enter() {
+            Ok(state) => {
+                let count = state.borrow.get();
+                assert!(count >= 0, "ThreadRefCell already mutably borrowed");
+                assert!(count < isize::MAX, "too many ThreadRefCell borrows");
+                state.borrow.set(count + 1);
+                Ref(self, PhantomData)
+            }
+            Err(owner) => self.0.acquire_failed(owner),
+        }
+    }
+
+    /// Tries to immutably borrow the value. Returns `None` when the cell is owned by another
+    /// thread or mutably borrowed.
+    #[track_caller]
+    pub fn try_borrow(&self) -> Option<Ref<'_, T>> {
+        let state = self.enter().ok()?;
+        let count = state.borrow.get();
+        if (0..isize::MAX).contains(&count) {
+            state.borrow.set(count + 1);
+            Some(Ref(self, PhantomData))
+        } else {
+            None
+        }
+    }
+
+    /// Mutably borrows the value.
+    ///
+    /// # Panics
+    ///
+    /// The cell is owned by another thread or already borrowed.
+    #[track_caller]
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        match self.enter() {
+            Ok(state) => {
+                assert!(state.borrow.get() == 0, "ThreadRefCell already borrowed");
+                state.borrow.set(-1);
+                RefMut(self, PhantomData)
+            }
+            Err(owner) => self.0.acquire_failed(owner),
+        }
+    }
+
+    /// Tries to mutably borrow the value. Returns `None` when the cell is owned by another
+    /// thread or already borrowed.
+    #[track_caller]
+    pub fn try_borrow_mut(&self) -> Option<RefMut<'_, T>> {
+        let state = self.enter().ok()?;
+        if state.borrow.get() == 0 {
+            state.borrow.set(-1);
+            Some(RefMut(self, PhantomData))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true when the current thread borrows the cell.
+    #[inline(always)]
+    pub fn is_owned(&self) -> bool {
+        self.0.is_owned()
+    }
+
+    /// Returns true when the cell is not borrowed by any thread. This is **inexact and racy**
+    /// in the same way as `ThreadCell::is_disowned()`.
+    #[inline(always)]
+    pub fn is_disowned(&self) -> bool {
+        self.0.is_disowned()
+    }
+
+    /// Gets a mutable reference to the value. No borrows can exist, thus this needs no
+    /// checks beside the ownership.
+    ///
+    /// # Panics
+    ///
+    /// Another thread owns the cell.
+    pub fn get_mut(&mut self) -> &mut T {
+        assert!(
+            self.0.is_disowned() || self.0.is_owned(),
+            "Thread has no access to ThreadCell"
+        );
+        // SAFETY: the cell is disowned or ours and `&mut self` excludes any borrows
+        unsafe { self.0.get_mut_unchecked() }.value.get_mut()
+    }
+
+    /// Consumes the cell and returns its value.
+    ///
+    /// # Panics
+    ///
+    /// Another thread owns the cell.
+    pub fn into_inner(self) -> T {
+        let cell = self.0;
+        if cell.is_disowned() {
+            cell.acquire();
+        }
+        cell.into_inner().value.into_inner()
+    }
+}
+
+impl<T: Default> Default for ThreadRefCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// An immutable borrow of a `ThreadRefCell`, the current thread owns the cell as long any
+/// borrow exists.
+pub struct Ref<'a, T>(&'a ThreadRefCell<T>, PhantomData<*const ()>);
+
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the borrow keeps the cell owned and mutable borrows out
+        unsafe { &*self.0 .0.get_unchecked().value.get() }
+    }
+}
+
+impl<T> Drop for Ref<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: the borrow keeps the cell owned
+        let state = unsafe { self.0 .0.get_unchecked() };
+        state.borrow.set(state.borrow.get() - 1);
+        self.0.leave(state);
+    }
+}
+
+/// A mutable borrow of a `ThreadRefCell`, the current thread owns the cell as long it
+/// exists.
+pub struct RefMut<'a, T>(&'a ThreadRefCell<T>, PhantomData<*const ()>);
+
+impl<T> Deref for RefMut<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the borrow keeps the cell owned and other borrows out
+        unsafe { &*self.0 .0.get_unchecked().value.get() }
+    }
+}
+
+impl<T> DerefMut for RefMut<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the borrow keeps the cell owned and other borrows out
+        unsafe { &mut *self.0 .0.get_unchecked().value.get() }
+    }
+}
+
+impl<T> Drop for RefMut<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: the borrow keeps the cell owned
+        let state = unsafe { self.0 .0.get_unchecked() };
+        state.borrow.set(0);
+        self.0.leave(state);
+    }
+}
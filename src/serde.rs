@@ -0,0 +1,72 @@
+//! `Serialize` and `Deserialize` support, enabled by the `serde` feature.
+//!
+//! Serializing a cell serializes its value when the current thread owns the cell, by
+//! acquire or a `Guard`, and fails with an error otherwise. While the current thread holds a
+//! `GuardMut` the guard is serialized instead. Deserializing a `ThreadCell` creates a cell
+//! owned by the current thread, wrap it in `Disowned` to create a disowned cell instead.
+
+use ::serde::de::{Deserialize, Deserializer};
+use ::serde::ser::{Error, Serialize, Serializer};
+
+use crate::{CurrentThread, Guard, GuardMut, OwnerCell, OwnerIdentity};
+
+/// Serializes the value of a cell. The value is only borrowed while serializing, unlike
+/// `get()` this does not mark it shared.
+///
+/// # Errors
+///
+/// The cell is not owned by the current thread or the current thread holds a `GuardMut` on
+/// it.
+impl<T: Serialize, I: OwnerIdentity> Serialize for OwnerCell<T, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_owned() {
+            // SAFETY: we own the cell and the reference does not outlive this call
+            unsafe { &*self.data.get() }.serialize(serializer)
+        } else {
+            Err(S::Error::custom("Thread has no access to ThreadCell"))
+        }
+    }
+}
+
+/// Serializes the value of the guarded cell.
+impl<T: Serialize, I: OwnerIdentity> Serialize for Guard<'_, T, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+/// Serializes the value of the exclusively guarded cell.
+impl<T: Serialize, I: OwnerIdentity> Serialize for GuardMut<'_, T, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+/// Deserializes a cell owned by the current thread.
+impl<'de, T: Deserialize<'de>, I: OwnerIdentity> Deserialize<'de> for OwnerCell<T, I> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(OwnerCell::new_owned)
+    }
+}
+
+/// Wrapper that deserializes into a disowned cell. Serializes like the wrapped cell.
+pub struct Disowned<T, I: OwnerIdentity = CurrentThread>(pub OwnerCell<T, I>);
+
+impl<T, I: OwnerIdentity> Disowned<T, I> {
+    /// Returns the wrapped cell.
+    pub fn into_inner(self) -> OwnerCell<T, I> {
+        self.0
+    }
+}
+
+impl<T: Serialize, I: OwnerIdentity> Serialize for Disowned<T, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, I: OwnerIdentity> Deserialize<'de> for Disowned<T, I> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(|data| Disowned(OwnerCell::new_disowned(data)))
+    }
+}
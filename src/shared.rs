@@ -0,0 +1,266 @@
+//! Read-only access to `Sync` values by threads not owning the cell.
+//!
+//! A cell can be read by any number of shared guards at once, the ownership word then holds
+//! the number of readers instead of an owner and the cell can not be acquired until the last
+//! reader is gone. A frozen cell can be read by any thread with `get()` and never be taken
+//! again.
+
+use core::ops::Deref;
+use core::sync::atomic::Ordering;
+
+#[cfg(feature = "std")]
+use crate::deferred;
+use crate::{
+    diagnostics, CurrentThread, OwnerCell, OwnerIdentity, OwnerWord, EXCLUSIVE_BIT, FLAGS, FROZEN,
+    GUARD_BIT, READERS_BIT, SHARED_BIT, STICKY,
+};
+
+impl<T: ?Sized, I: OwnerIdentity> OwnerCell<T, I> {
+    /// Gets an immutable reference to the value from any thread, owner or not. Since `T` is
+    /// `Sync` reading it concurrently is fine as long as nobody mutates it through a shared
+    /// reference. Thus from then on the cell refuses `acquire_guard_mut()`, `with_mut()`,
+    /// pinned guards and `spawn_with()` for good. The owner can still use `get()` and
+    /// mutate the value through `&mut self`, which can't happen while shared references
+    /// exist.
+    ///
+    /// # Panics
+    ///
+    /// When a thread holds a `GuardMut` on the cell or it is in transit to a spawned thread.
+    #[inline]
+    #[track_caller]
+    pub fn get_shared(&self) -> &T
+    where
+        T: Sync,
+    {
+        self.try_get_shared()
+            .expect("ThreadCell is exclusively guarded")
+    }
+
+    /// Tries to get an immutable reference to the value from any thread, see
+    /// `get_shared()`. Returns `None` when a thread holds a `GuardMut` on the cell or it is
+    /// in transit to a spawned thread.
+    pub fn try_get_shared(&self) -> Option<&T>
+    where
+        T: Sync,
+    {
+        let mut word = self.thread_id.load(Ordering::Acquire);
+        while word & SHARED_BIT == 0 {
+            if word & EXCLUSIVE_BIT != 0 {
+                return None;
+            }
+            match self.thread_id.compare_exchange(
+                word,
+                word | SHARED_BIT,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(current) => word = current,
+            }
+        }
+        // SAFETY: `T` is `Sync` and the flag keeps any `&mut T` from being handed out
+        // through a shared reference
+        Some(unsafe { &*self.data.get() })
+    }
+
+    /// Acquires a `ThreadCell` for reading returning a `SharedGuard` that releases it when
+    /// becoming dropped. Any number of threads can hold shared guards at once, meanwhile the
+    /// cell can not be acquired by anyone.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is owned by any thread, including the current one.
+    #[inline]
+    #[track_caller]
+    pub fn acquire_shared_guard(&self) -> SharedGuard<'_, T, I>
+    where
+        T: Sync,
+    {
+        if let Err(owner) = self.take_shared() {
+            self.acquire_failed(owner);
+        }
+        SharedGuard(self)
+    }
+
+    /// Acquires a `ThreadCell` for reading returning a `Option<SharedGuard>` that releases
+    /// it when becoming dropped. Returns `None` when self is owned by any thread.
+    #[inline]
+    #[track_caller]
+    pub fn try_acquire_shared_guard(&self) -> Option<SharedGuard<'_, T, I>>
+    where
+        T: Sync,
+    {
+        if self.take_shared().is_ok() {
+            Some(SharedGuard(self))
+        } else {
+            None
+        }
+    }
+
+    /// Runs a closure on a `ThreadCell` with a shared guard.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is owned by any thread.
+    #[track_caller]
+    pub fn with_shared<R, F: FnOnce(&T) -> R>(&self, f: F) -> R
+    where
+        T: Sync,
+    {
+        f(&*self.acquire_shared_guard())
+    }
+
+    /// Tries to run a closure on a `ThreadCell` with a shared guard. Returns `Some(Result)`
+    /// when the cell could be acquired and None when it is owned by any thread.
+    #[track_caller]
+    pub fn try_with_shared<R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R>
+    where
+        T: Sync,
+    {
+        Some(f(&*self.try_acquire_shared_guard()?))
+    }
+
+    /// Returns true when shared guards read this `ThreadCell`. Like `is_disowned()` this is
+    /// **inexact and racy** unless the current thread holds a shared guard.
+    #[inline(always)]
+    pub fn is_shared(&self) -> bool {
+        self.thread_id.load(Ordering::Relaxed) & READERS_BIT != 0
+    }
+
+    /// Freezes a disowned cell or one acquired by the current thread for good. From then on
+    /// every thread can read the value with `get()` and friends while nobody can acquire
+    /// the cell or mutate the value anymore, not even through `&mut self`. This is a no-op
+    /// when the cell is frozen already.
+    ///
+    /// This publishes a value which was set up by one thread, e.g. configuration in a
+    /// static, for all threads without any further ownership checks.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is owned by another thread, guarded or read by shared guards.
+    #[track_caller]
+    pub fn freeze(&self)
+    where
+        T: Sync,
+    {
+        let current = I::current().get();
+        let mut word = self.thread_id.load(Ordering::Relaxed);
+        while word != FROZEN {
+            let owner = word & !STICKY;
+            if owner != 0 && owner != current {
+                if owner & GUARD_BIT != 0 && owner & !FLAGS == current {
+                    panic!("Thread can not freeze guarded ThreadCell");
+                }
+                self.acquire_failed(word);
+            }
+            match self
+                .thread_id
+                .compare_exchange(word, FROZEN, Ordering::AcqRel, Ordering::Relaxed)
+            {
+                Ok(_) => diagnostics::forget(self.addr(), owner),
+                Err(changed) => word = changed,
+            }
+        }
+    }
+
+    /// Returns true when the cell is frozen and can be read by every thread, see
+    /// `freeze()`.
+    #[inline(always)]
+    pub fn is_frozen(&self) -> bool {
+        // pairs with freezing, the value is visible when frozen
+        self.thread_id.load(Ordering::Acquire) == FROZEN
+    }
+
+    /// Adds a reader to a disowned or shared cell. Returns the current ownership word when
+    /// the cell is owned.
+    #[inline]
+    fn take_shared(&self) -> Result<(), u64> {
+        #[cfg(feature = "std")]
+        deferred::run::<I>();
+        let mut word = self.thread_id.load(Ordering::Relaxed);
+        loop {
+            let readers = if word & READERS_BIT != 0 {
+                word & !FLAGS
+            } else if word & !STICKY == 0 {
+                0
+            } else {
+                return Err(word);
+            };
+            assert!(
+                readers < I::Word::MAX_ID,
+                "Too many shared guards on ThreadCell"
+            );
+            match self.thread_id.compare_exchange(
+                word,
+                READERS_BIT | word & STICKY | (readers + 1),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(current) => word = current,
+            }
+        }
+    }
+
+    /// Adds a reader to a cell that is read by shared guards already. Returns `None` when it
+    /// is not.
+    #[cfg(feature = "std")]
+    pub(crate) fn try_join_shared(&self) -> Option<SharedGuard<'_, T, I>> {
+        let mut word = self.thread_id.load(Ordering::Relaxed);
+        while word & READERS_BIT != 0 {
+            assert!(
+                word & !FLAGS < I::Word::MAX_ID,
+                "Too many shared guards on ThreadCell"
+            );
+            match self.thread_id.compare_exchange(
+                word,
+                word + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                // only `Sync` values are ever read by shared guards, thus this one is
+                Ok(_) => return Some(SharedGuard(self)),
+                Err(current) => word = current,
+            }
+        }
+        None
+    }
+
+    /// Removes a reader, the last one disowns the cell.
+    fn release_shared(&self) {
+        let mut word = self.thread_id.load(Ordering::Relaxed);
+        loop {
+            debug_assert!(word & READERS_BIT != 0);
+            let last = word & !FLAGS == 1;
+            let new = if last { word & STICKY } else { word - 1 };
+            match self
+                .thread_id
+                .compare_exchange(word, new, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => word = current,
+            }
+        }
+    }
+}
+
+/// Guard for reading a `Sync` value, see `acquire_shared_guard()`. While shared guards
+/// exist the cell can not be acquired. Unlike `Guard` it is not bound to a thread.
+pub struct SharedGuard<'a, T: ?Sized, I: OwnerIdentity = CurrentThread>(&'a OwnerCell<T, I>);
+
+/// Removes the reader from the referenced `ThreadCell`, the last one disowns it.
+impl<T: ?Sized, I: OwnerIdentity> Drop for SharedGuard<'_, T, I> {
+    fn drop(&mut self) {
+        self.0.release_shared();
+    }
+}
+
+impl<T: ?Sized, I: OwnerIdentity> Deref for SharedGuard<'_, T, I> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: shared guards are only created for `Sync` values and nobody can acquire
+        // the cell while they exist
+        unsafe { &*self.0.data.get() }
+    }
+}
@@ -0,0 +1,200 @@
+//! Spawning threads that take over the ownership of a `ThreadCell`.
+//!
+//! The caller hands its `GuardMut` over, the cell is in transit while the spawned thread
+//! starts and after it finished, no other thread can acquire it meanwhile. The spawned thread
+//! owns the cell exclusively while its closure runs, joining gives a `GuardMut` to the
+//! joining thread.
+
+use core::mem;
+use std::thread::{self, Scope};
+
+use crate::{GuardMut, ThreadCell, EXCLUSIVE_BIT, GUARD_BIT};
+
+/// Spawns a thread running `f` on the value of the cell `guard` holds. The guard is consumed,
+/// the cell is owned by the spawned thread while `f` runs and by the thread joining the
+/// returned handle afterwards.
+///
+/// # Panics
+///
+/// The thread can not be spawned, the cell is disowned then.
+#[track_caller]
+pub fn spawn_with<T, F, R>(guard: GuardMut<'static, T>, f: F) -> JoinHandle<T, R>
+where
+    T: ?Sized + Send + 'static,
+    F: FnOnce(&mut T) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let cell = hand_off(guard);
+    let thread = thread::Builder::new()
+        .spawn(move || run(cell, f))
+        .unwrap_or_else(|err| {
+            drop(receive(cell));
+            panic!("failed to spawn thread: {err}")
+        });
+    JoinHandle {
+        cell,
+        thread: Some(thread),
+    }
+}
+
+/// Spawns a scoped thread running `f` on the value of the cell `guard` holds, see
+/// `spawn_with()`.
+///
+/// # Panics
+///
+/// The thread can not be spawned, the cell is disowned then.
+#[track_caller]
+pub fn spawn_scoped_with<'scope, T, F, R>(
+    scope: &'scope Scope<'scope, '_>,
+    guard: GuardMut<'scope, T>,
+    f: F,
+) -> ScopedJoinHandle<'scope, T, R>
+where
+    T: ?Sized + Send,
+    F: FnOnce(&mut T) -> R + Send + 'scope,
+    R: Send + 'scope,
+{
+    let cell = hand_off(guard);
+    let thread = thread::Builder::new()
+        .spawn_scoped(scope, move || run(cell, f))
+        .unwrap_or_else(|err| {
+            drop(receive(cell));
+            panic!("failed to spawn thread: {err}")
+        });
+    ScopedJoinHandle {
+        cell,
+        thread: Some(thread),
+    }
+}
+
+/// Puts the cell of `guard` in transit without releasing it.
+fn hand_off<'a, T: ?Sized>(guard: GuardMut<'a, T>) -> &'a ThreadCell<T> {
+    let cell = guard.0;
+    mem::forget(guard);
+    // SAFETY: the guard owned the cell exclusively, it is gone now
+    unsafe { cell.send_off() };
+    cell
+}
+
+/// Takes the cell in transit as the joining thread.
+#[track_caller]
+fn receive<T: ?Sized>(cell: &ThreadCell<T>) -> GuardMut<'_, T> {
+    cell.receive(GUARD_BIT | EXCLUSIVE_BIT);
+    GuardMut(cell)
+}
+
+/// Runs on the spawned thread, taking the cell exclusively for `f`.
+fn run<T: ?Sized, R>(cell: &ThreadCell<T>, f: impl FnOnce(&mut T) -> R) -> R {
+    cell.receive(GUARD_BIT | EXCLUSIVE_BIT);
+    let handback = Handback(cell);
+    // SAFETY: the exclusive ownership refuses any other access to the value
+    f(unsafe { &mut *handback.0.data.get() })
+}
+
+/// Puts the cell in transit to the joining thread when the closure returns or panics.
+struct Handback<'a, T: ?Sized>(&'a ThreadCell<T>);
+
+impl<T: ?Sized> Drop for Handback<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: the spawned thread owns the cell and returns from here
+        unsafe { self.0.send_off() };
+    }
+}
+
+/// Owned permission to join a thread spawned by `spawn_with()`.
+///
+/// Dropping the handle waits for the thread to finish and leaves the cell disowned.
+pub struct JoinHandle<T: ?Sized + 'static, R> {
+    cell: &'static ThreadCell<T>,
+    thread: Option<thread::JoinHandle<R>>,
+}
+
+impl<T: ?Sized, R> JoinHandle<T, R> {
+    /// Waits for the thread to finish and returns a `GuardMut` on the cell to the current
+    /// thread, also when the thread panicked. Returns the result of the closure or the panic
+    /// payload along.
+    #[track_caller]
+    pub fn join(mut self) -> (GuardMut<'static, T>, thread::Result<R>) {
+        let result = self.wait();
+        (receive(self.cell), result)
+    }
+
+    /// Returns true when the thread finished running its closure.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.handle().is_finished()
+    }
+
+    /// Returns the spawned thread.
+    #[must_use]
+    pub fn thread(&self) -> &thread::Thread {
+        self.handle().thread()
+    }
+
+    // The handle is only taken by joining, which consumes or drops self.
+    fn handle(&self) -> &thread::JoinHandle<R> {
+        self.thread.as_ref().expect("thread already joined")
+    }
+
+    fn wait(&mut self) -> thread::Result<R> {
+        self.thread.take().expect("thread already joined").join()
+    }
+}
+
+impl<T: ?Sized, R> Drop for JoinHandle<T, R> {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            let _ = self.wait();
+            drop(receive(self.cell));
+        }
+    }
+}
+
+/// Owned permission to join a scoped thread spawned by `spawn_scoped_with()`.
+///
+/// Dropping the handle waits for the thread to finish and leaves the cell disowned.
+pub struct ScopedJoinHandle<'scope, T: ?Sized, R> {
+    cell: &'scope ThreadCell<T>,
+    thread: Option<thread::ScopedJoinHandle<'scope, R>>,
+}
+
+impl<'scope, T: ?Sized, R> ScopedJoinHandle<'scope, T, R> {
+    /// Waits for the thread to finish and returns a `GuardMut` on the cell to the current
+    /// thread, also when the thread panicked. Returns the result of the closure or the panic
+    /// payload along.
+    #[track_caller]
+    pub fn join(mut self) -> (GuardMut<'scope, T>, thread::Result<R>) {
+        let result = self.wait();
+        (receive(self.cell), result)
+    }
+
+    /// Returns true when the thread finished running its closure.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.handle().is_finished()
+    }
+
+    /// Returns the spawned thread.
+    #[must_use]
+    pub fn thread(&self) -> &thread::Thread {
+        self.handle().thread()
+    }
+
+    // The handle is only taken by joining, which consumes or drops self.
+    fn handle(&self) -> &thread::ScopedJoinHandle<'_, R> {
+        self.thread.as_ref().expect("thread already joined")
+    }
+
+    fn wait(&mut self) -> thread::Result<R> {
+        self.thread.take().expect("thread already joined").join()
+    }
+}
+
+impl<T: ?Sized, R> Drop for ScopedJoinHandle<'_, T, R> {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            let _ = self.wait();
+            drop(receive(self.cell));
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Declaring static cells with safe accessors.
+
+/// Declares statics that are `ThreadCells` and accessed through generated functions.
+///
+/// Each declaration expands to a unit struct of the given name holding the cell in a hidden
+/// static. Its associated functions `with()`, `with_mut()`, `try_with()` and `try_with_mut()`
+/// acquire the cell for the duration of a closure, `cell()` returns the `ThreadCell` itself.
+#[macro_export]
+macro_rules! static_cell {
+    ($($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr;)*) => {$(
+        $(#[$attr])*
+        #[allow(non_camel_case_types)]
+        $vis struct $name;
+
+        #[allow(dead_code)]
+        impl $name {
+            /// Returns the underlying `ThreadCell`.
+            #[inline]
+            $vis fn cell() -> &'static $crate::ThreadCell<$ty> {
+                static CELL: $crate::ThreadCell<$ty> = $crate::ThreadCell::new_disowned($init);
+                &CELL
+            }
+
+            /// Runs a closure on the value with acquire/release.
+            ///
+            /// # Panics
+            ///
+            /// When the cell is already owned by the current thread or is owned by another
+            /// thread.
+            #[track_caller]
+            $vis fn with<R>(f: impl FnOnce(&$ty) -> R) -> R {
+                Self::cell().with(f)
+            }
+
+            /// Runs a closure on the mutable value with acquire/release.
+            ///
+            /// # Panics
+            ///
+            /// When the cell is already owned by the current thread or is owned by another
+            /// thread.
+            #[track_caller]
+            $vis fn with_mut<R>(f: impl FnOnce(&mut $ty) -> R) -> R {
+                Self::cell().with_mut(f)
+            }
+
+            /// Tries to run a closure on the value with acquire/release. Returns None when
+            /// the cell is owned by another thread.
+            #[track_caller]
+            $vis fn try_with<R>(f: impl FnOnce(&$ty) -> R) -> Option<R> {
+                Self::cell().try_with(f)
+            }
+
+            /// Tries to run a closure on the mutable value with acquire/release. Returns None
+            /// when the cell is owned by another thread.
+            #[track_caller]
+            $vis fn try_with_mut<R>(f: impl FnOnce(&mut $ty) -> R) -> Option<R> {
+                Self::cell().try_with_mut(f)
+            }
+        }
+    )*};
+}
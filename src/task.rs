@@ -0,0 +1,109 @@
+//! Cells owned by async tasks instead of threads.
+//!
+//! Work stealing executors move tasks between threads, thus a `ThreadCell` acquired before
+//! an `.await` may not be accessible anymore after it. A `TaskCell` is owned by the current
+//! task instead. Tasks are made known by wrapping their futures in `task::scope()`, which
+//! works with any executor:
+//!
+//! ```
+//! use threadcell::task::{self, TaskCell};
+//!
+//! static CELL: TaskCell<i32> = TaskCell::new_disowned(234);
+//!
+//! let future = task::scope(async {
+//!     let guard = CELL.acquire_guard();
+//!     // ... .await points, the task may resume on another thread
+//!     *guard
+//! });
+//! # drop(future);
+//! ```
+//!
+//! Code not running inside a task scope is identified by its thread, `TaskCells` then
+//! behave like `ThreadCells`.
+
+use std::cell::Cell;
+use std::future::Future;
+use std::num::NonZeroU64;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use crate::{CurrentThread, OwnerCell, OwnerIdentity, OwnerWord};
+
+/// A cell that can be owned by a single async task or none at all.
+pub type TaskCell<T> = OwnerCell<T, CurrentTask>;
+
+/// Identifies owners by the task scope they are polled in, or by their thread when not
+/// running inside a task scope.
+pub struct CurrentTask;
+
+thread_local! {
+    static CURRENT_TASK: Cell<Option<NonZeroU64>> = const { Cell::new(None) };
+    static THREAD_FALLBACK: NonZeroU64 = next_id();
+}
+
+/// Task ids and the per thread fallback ids share one counter so they never collide.
+fn next_id() -> NonZeroU64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(1);
+    let id = NonZeroU64::new(COUNTER.fetch_add(1, Ordering::Relaxed)).unwrap();
+    assert!(
+        id.get() <= <CurrentTask as OwnerIdentity>::Word::MAX_ID,
+        "too many tasks"
+    );
+    id
+}
+
+unsafe impl OwnerIdentity for CurrentTask {
+    type Word = <CurrentThread as OwnerIdentity>::Word;
+
+    #[inline]
+    #[mutants::skip]
+    fn current() -> NonZeroU64 {
+        CURRENT_TASK
+            .with(Cell::get)
+            .unwrap_or_else(|| THREAD_FALLBACK.with(|&id| id))
+    }
+}
+
+/// Runs `future` as its own task. Each time the returned future is polled the current task
+/// is set to it, `TaskCells` acquired there stay owned by the task no matter on which thread
+/// it is polled next.
+pub fn scope<F: Future>(future: F) -> TaskScope<F> {
+    TaskScope {
+        id: next_id(),
+        future,
+    }
+}
+
+/// Future returned by `scope()`.
+pub struct TaskScope<F> {
+    id: NonZeroU64,
+    future: F,
+}
+
+impl<F> TaskScope<F> {
+    /// Returns the task id used for `TaskCells` owned by this task.
+    pub fn id(&self) -> NonZeroU64 {
+        self.id
+    }
+}
+
+/// Restores the task that was current before entering a `TaskScope`, even on panic.
+struct Restore(Option<NonZeroU64>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        CURRENT_TASK.with(|current| current.set(self.0));
+    }
+}
+
+impl<F: Future> Future for TaskScope<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: structural pinning, `future` is never moved out of the pinned `TaskScope`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let _restore = Restore(CURRENT_TASK.with(|current| current.replace(Some(this.id))));
+        unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx)
+    }
+}
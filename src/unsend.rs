@@ -0,0 +1,166 @@
+//! Moving whole `!Send` object graphs between threads.
+
+use crate::ThreadCell;
+
+/// A `ThreadCell` that is `Send` and `Sync` for any `T`. This allows handing a complete
+/// `Rc` based object graph from thread to thread, the cell makes sure only one thread at a
+/// time accesses it.
+///
+/// Ownership is handled like with a `ThreadCell`. Access to the value is unsafe since
+/// safe code could take things out of it that must not leave the cell.
+pub struct UnsendCell<T>(ThreadCell<T>);
+
+// SAFETY: the constructors and accessors contracts make the cell the sole entry point into
+// the value
+unsafe impl<T> Send for UnsendCell<T> {}
+unsafe impl<T> Sync for UnsendCell<T> {}
+
+impl<T> UnsendCell<T> {
+    const_fn! {
+        /// Creates a `UnsendCell` that is not owned by any thread. This is a const fn which
+        /// allows static construction.
+        ///
+        /// # Safety
+        ///
+        /// The cell must be the sole entry point into the value. Nothing reachable from it
+        /// may be reachable by other means, e.g. no clone of a `Rc` within the value may be
+        /// kept outside. Thread local state the value depends on must not be used either.
+        pub const unsafe fn new_disowned(value: T) -> Self {
+            UnsendCell(ThreadCell::new_disowned(value))
+        }
+    }
+
+    /// Creates a `UnsendCell` that is owned by the current thread.
+    ///
+    /// # Safety
+    ///
+    /// Same as `new_disowned()`, the cell must be the sole entry point into the value.
+    pub unsafe fn new_owned(value: T) -> Self {
+        UnsendCell(ThreadCell::new_owned(value))
+    }
+
+    /// Takes the ownership of the cell.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is already owned by this thread or it is owned by another thread.
+    #[track_caller]
+    #[inline]
+    pub fn acquire(&self) {
+        self.0.acquire();
+    }
+
+    /// Tries to take the ownership of the cell. Returns true when the ownership could be
+    /// obtained or the cell was already owned by the current thread and false when the cell
+    /// is owned by another thread.
+    #[track_caller]
+    #[inline]
+    pub fn try_acquire(&self) -> bool {
+        self.0.try_acquire()
+    }
+
+    /// Sets the cell which is owned by the current thread into the disowned state.
+    ///
+    /// # Safety
+    ///
+    /// The current thread must not use any references it has to the value after releasing
+    /// it.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    #[inline]
+    pub unsafe fn release(&self) {
+        self.0.release();
+    }
+
+    /// Tries to set the cell which is owned by the current thread into the disowned state.
+    /// Returns *true* on success and *false* when the current thread does not own the cell.
+    #[inline]
+    pub fn try_release(&self) -> bool {
+        self.0.try_release()
+    }
+
+    /// Returns true when the current thread owns the cell.
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.0.is_owned()
+    }
+
+    /// Returns true when the cell is not owned by any thread. This is **inexact and racy**,
+    /// see `ThreadCell::is_disowned()`.
+    #[inline]
+    pub fn is_disowned(&self) -> bool {
+        self.0.is_disowned()
+    }
+
+    /// Gets an immutable reference to the value of a cell the current thread owns.
+    ///
+    /// # Safety
+    ///
+    /// The reference must not be used after the cell is released. Nothing reachable from
+    /// the value may be kept or made reachable by other means, e.g. no clone of a `Rc`
+    /// within the value may be stored outside of it.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    #[inline]
+    pub unsafe fn get(&self) -> &T {
+        // Not marked shared, the contract keeps the reference from outliving the ownership
+        self.0.assert_owned();
+        &*self.0.data.get()
+    }
+
+    /// Gets a mutable reference to the value of a cell the current thread owns.
+    ///
+    /// # Safety
+    ///
+    /// Same as `get()`, nothing reachable from the value may escape it.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    #[inline]
+    pub unsafe fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut()
+    }
+
+    /// Runs a closure on the value with acquire/release.
+    ///
+    /// # Safety
+    ///
+    /// Same as `get()`, nothing reachable from the value may escape the closure.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is already owned by the current thread or is owned by another thread.
+    #[track_caller]
+    pub unsafe fn with<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        self.0.with(f)
+    }
+
+    /// Runs a closure on the value with acquire/release and mutable access.
+    ///
+    /// # Safety
+    ///
+    /// Same as `get()`, nothing reachable from the value may escape the closure.
+    ///
+    /// # Panics
+    ///
+    /// When the cell is already owned by the current thread or is owned by another thread.
+    #[track_caller]
+    pub unsafe fn with_mut<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        self.0.with_mut(f)
+    }
+
+    /// Consumes a owned cell and returns its value.
+    ///
+    /// # Panics
+    ///
+    /// The current thread does not own the cell.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
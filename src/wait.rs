@@ -0,0 +1,162 @@
+//! Support for blocking acquisition. Threads waiting for a cell register an edge from
+//! themselves to the current owner in a global wait-for graph. When such an edge closes a
+//! cycle the threads involved would wait forever, the newest waiter in that cycle then fails
+//! with a `Deadlock` error. Owners are keyed by their `OwnerIdentity` type and id since ids
+//! of different identities are unrelated.
+
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use std::{error, fmt, thread};
+
+/// Error returned from the blocking acquire functions when waiting would deadlock. Lists the
+/// threads that wait on each other, starting with the thread that got this error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deadlock {
+    cycle: Vec<(u64, Option<String>)>,
+}
+
+impl Deadlock {
+    /// Returns the ids of the threads forming the cycle. The first one is the thread which
+    /// got this error, each thread waits for a cell owned by the next one and the last one
+    /// waits for the first one. These are the same ids a `ThreadCell` uses to identify its
+    /// owner, their values are otherwise unspecified.
+    pub fn thread_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.cycle.iter().map(|(id, _)| *id)
+    }
+}
+
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.write_str("Deadlock waiting for ThreadCell:")?;
+        for (n, (id, name)) in self.cycle.iter().chain(self.cycle.first()).enumerate() {
+            if n > 0 {
+                f.write_str(" ->")?;
+            }
+            match name {
+                Some(name) => write!(f, " `{name}`")?,
+                None => write!(f, " #{id}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for Deadlock {}
+
+struct Edge {
+    holder: u64,
+    seq: u64,
+    name: Option<String>,
+}
+
+struct WaitGraph {
+    seq: u64,
+    edges: BTreeMap<(TypeId, u64), Edge>,
+}
+
+static WAIT_GRAPH: Mutex<WaitGraph> = Mutex::new(WaitGraph {
+    seq: 0,
+    edges: BTreeMap::new(),
+});
+
+/// Registers that `waiter` waits for a cell owned by `holder`. Fails when this closes a
+/// cycle in which `waiter` is the newest waiter, the edge is then removed again.
+pub(crate) fn waiting(identity: TypeId, waiter: u64, holder: u64) -> Result<(), Deadlock> {
+    let waiter = (identity, waiter);
+    let mut graph = WAIT_GRAPH.lock().unwrap_or_else(PoisonError::into_inner);
+    let WaitGraph { seq, edges } = &mut *graph;
+
+    match edges.get_mut(&waiter) {
+        Some(edge) if edge.holder == holder => {}
+        Some(edge) => {
+            *seq += 1;
+            edge.holder = holder;
+            edge.seq = *seq;
+        }
+        None => {
+            *seq += 1;
+            edges.insert(
+                waiter,
+                Edge {
+                    holder,
+                    seq: *seq,
+                    name: thread::current().name().map(String::from),
+                },
+            );
+        }
+    }
+
+    // Follow the chain of waiting threads, when it leads back to us we found a cycle. Chains
+    // that run into a cycle not involving us are bounded by the number of edges.
+    let mut newest = edges[&waiter].seq;
+    let mut current = (identity, holder);
+    for _ in 0..edges.len() {
+        if current == waiter {
+            if newest != edges[&waiter].seq {
+                // Some other waiter in the cycle is newer, it will bail out.
+                return Ok(());
+            }
+            let mut cycle = Vec::new();
+            let mut current = waiter;
+            loop {
+                let edge = &edges[&current];
+                cycle.push((current.1, edge.name.clone()));
+                current = (identity, edge.holder);
+                if current == waiter {
+                    break;
+                }
+            }
+            edges.remove(&waiter);
+            return Err(Deadlock { cycle });
+        }
+        match edges.get(&current) {
+            Some(edge) => {
+                newest = newest.max(edge.seq);
+                current = (identity, edge.holder);
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+/// Removes the edge of a thread that stopped waiting.
+pub(crate) fn done(identity: TypeId, waiter: u64) {
+    WAIT_GRAPH
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .edges
+        .remove(&(identity, waiter));
+}
+
+/// Backoff strategy for threads polling a cell: spins first, then yields and finally sleeps
+/// with exponentially increasing durations.
+pub(crate) struct Backoff(u32);
+
+impl Backoff {
+    const SPIN_LIMIT: u32 = 6;
+    const YIELD_LIMIT: u32 = 10;
+    const MAX_SLEEP: Duration = Duration::from_millis(1);
+
+    pub(crate) fn new() -> Self {
+        Backoff(0)
+    }
+
+    /// Waits for some time, at most for `limit`.
+    pub(crate) fn snooze(&mut self, limit: Option<Duration>) {
+        if self.0 < Self::SPIN_LIMIT {
+            for _ in 0..1 << self.0 {
+                std::hint::spin_loop();
+            }
+        } else if self.0 < Self::YIELD_LIMIT {
+            thread::yield_now();
+        } else {
+            let sleep = Duration::from_micros(1 << (self.0 - Self::YIELD_LIMIT).min(10))
+                .min(Self::MAX_SLEEP);
+            thread::sleep(limit.map_or(sleep, |limit| sleep.min(limit)));
+        }
+        self.0 = self.0.saturating_add(1);
+    }
+}
@@ -0,0 +1,205 @@
+//! The atomic word storing the owner of a cell.
+//!
+//! Internally ownership is handled as `u64` with the flag bits (see `FLAGS`) at the top.
+//! Narrower words keep the flag bits at their top as well and store the id in the remaining
+//! lower bits, thus ids must not exceed `OwnerWord::MAX_ID` of the word used.
+
+#[cfg(all(not(loom), target_has_atomic = "64"))]
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+#[cfg(loom)]
+use loom::sync::atomic::AtomicU64;
+
+use crate::FLAGS;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Atomic integer types which can store the owner of an `OwnerCell`. This is selected by
+/// `OwnerIdentity::Word`. Implemented for `AtomicU64`, `AtomicU32` and `AtomicUsize`.
+pub trait OwnerWord: sealed::Sealed + Sized {
+    /// The largest owner id that can be stored.
+    const MAX_ID: u64;
+
+    /// A word for a disowned cell.
+    #[cfg(not(loom))]
+    #[doc(hidden)]
+    #[allow(clippy::declare_interior_mutable_const)]
+    const DISOWNED: Self;
+
+    #[doc(hidden)]
+    fn new(value: u64) -> Self;
+
+    #[doc(hidden)]
+    fn load(&self, order: Ordering) -> u64;
+
+    #[doc(hidden)]
+    fn store(&self, value: u64, order: Ordering);
+
+    #[doc(hidden)]
+    fn compare_exchange(
+        &self,
+        current: u64,
+        new: u64,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<u64, u64>;
+}
+
+#[cfg(any(loom, target_has_atomic = "64"))]
+impl sealed::Sealed for AtomicU64 {}
+
+#[cfg(any(loom, target_has_atomic = "64"))]
+impl OwnerWord for AtomicU64 {
+    const MAX_ID: u64 = !FLAGS;
+
+    #[cfg(not(loom))]
+    const DISOWNED: Self = AtomicU64::new(0);
+
+    #[inline(always)]
+    fn new(value: u64) -> Self {
+        AtomicU64::new(value)
+    }
+
+    #[inline(always)]
+    fn load(&self, order: Ordering) -> u64 {
+        AtomicU64::load(self, order)
+    }
+
+    #[inline(always)]
+    fn store(&self, value: u64, order: Ordering) {
+        AtomicU64::store(self, value, order);
+    }
+
+    #[inline(always)]
+    fn compare_exchange(
+        &self,
+        current: u64,
+        new: u64,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<u64, u64> {
+        AtomicU64::compare_exchange(self, current, new, success, failure)
+    }
+}
+
+/// Implements `OwnerWord` for narrower atomics by moving the flag bits to their top.
+macro_rules! narrow_owner_word {
+    ($module:ident, $atomic:ty, $int:ty) => {
+        mod $module {
+            use super::*;
+
+            const SHIFT: u32 = u64::BITS - <$int>::BITS;
+
+            #[inline(always)]
+            fn pack(value: u64) -> $int {
+                debug_assert!(
+                    value & !FLAGS <= <$atomic as OwnerWord>::MAX_ID,
+                    "owner id too big"
+                );
+                ((value & FLAGS) >> SHIFT | value & !FLAGS) as $int
+            }
+
+            #[inline(always)]
+            fn unpack(value: $int) -> u64 {
+                let value = value as u64;
+                (value << SHIFT) & FLAGS | value & !(FLAGS >> SHIFT)
+            }
+
+            impl sealed::Sealed for $atomic {}
+
+            impl OwnerWord for $atomic {
+                const MAX_ID: u64 = !(FLAGS >> SHIFT) & <$int>::MAX as u64;
+
+                #[cfg(not(loom))]
+                const DISOWNED: Self = <$atomic>::new(0);
+
+                #[inline(always)]
+                fn new(value: u64) -> Self {
+                    <$atomic>::new(pack(value))
+                }
+
+                #[inline(always)]
+                fn load(&self, order: Ordering) -> u64 {
+                    unpack(<$atomic>::load(self, order))
+                }
+
+                #[inline(always)]
+                fn store(&self, value: u64, order: Ordering) {
+                    <$atomic>::store(self, pack(value), order);
+                }
+
+                #[inline(always)]
+                fn compare_exchange(
+                    &self,
+                    current: u64,
+                    new: u64,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<u64, u64> {
+                    <$atomic>::compare_exchange(self, pack(current), pack(new), success, failure)
+                        .map(unpack)
+                        .map_err(unpack)
+                }
+            }
+        }
+    };
+}
+
+narrow_owner_word!(narrow_u32, AtomicU32, u32);
+#[cfg(not(target_pointer_width = "64"))]
+narrow_owner_word!(narrow_usize, AtomicUsize, usize);
+
+#[cfg(target_pointer_width = "64")]
+impl sealed::Sealed for AtomicUsize {}
+
+#[cfg(target_pointer_width = "64")]
+impl OwnerWord for AtomicUsize {
+    const MAX_ID: u64 = !FLAGS;
+
+    #[cfg(not(loom))]
+    const DISOWNED: Self = AtomicUsize::new(0);
+
+    #[inline(always)]
+    fn new(value: u64) -> Self {
+        AtomicUsize::new(value as usize)
+    }
+
+    #[inline(always)]
+    fn load(&self, order: Ordering) -> u64 {
+        AtomicUsize::load(self, order) as u64
+    }
+
+    #[inline(always)]
+    fn store(&self, value: u64, order: Ordering) {
+        AtomicUsize::store(self, value as usize, order);
+    }
+
+    #[inline(always)]
+    fn compare_exchange(
+        &self,
+        current: u64,
+        new: u64,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<u64, u64> {
+        AtomicUsize::compare_exchange(self, current as usize, new as usize, success, failure)
+            .map(|value| value as u64)
+            .map_err(|value| value as u64)
+    }
+}
+
+/// Creates the word of a disowned cell.
+#[cfg(not(loom))]
+#[inline(always)]
+pub(crate) const fn disowned<W: OwnerWord>() -> W {
+    W::DISOWNED
+}
+
+/// Creates the word of a disowned cell.
+#[cfg(loom)]
+#[inline(always)]
+pub(crate) fn disowned<W: OwnerWord>() -> W {
+    W::new(0)
+}
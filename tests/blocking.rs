@@ -0,0 +1,131 @@
+#![cfg(feature = "std")]
+use std::sync::Barrier;
+use std::time::Duration;
+use threadcell::ThreadCell;
+
+#[test]
+fn acquire_wait() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(234);
+    let barrier = Barrier::new(2);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let guard = CELL.acquire_guard();
+            barrier.wait();
+            std::thread::sleep(Duration::from_millis(10));
+            drop(guard);
+        });
+
+        barrier.wait();
+        CELL.acquire_wait().expect("acquired");
+        assert_eq!(*CELL.get(), 234);
+        unsafe { CELL.release() };
+    });
+}
+
+#[test]
+fn try_acquire_wait_timeout() {
+    let cell = ThreadCell::new_disowned(234);
+    let barrier = Barrier::new(2);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let _guard = cell.acquire_guard();
+            barrier.wait();
+            barrier.wait();
+        });
+
+        barrier.wait();
+        assert_eq!(cell.try_acquire_wait(Duration::from_millis(10)), Ok(false));
+        barrier.wait();
+    });
+
+    assert_eq!(cell.try_acquire_wait(Duration::from_millis(10)), Ok(true));
+}
+
+#[test]
+fn acquire_guard_wait() {
+    let cell = ThreadCell::new_disowned(234);
+    assert_eq!(*cell.acquire_guard_wait().expect("acquired"), 234);
+    assert!(cell.is_disowned());
+}
+
+#[test]
+fn self_deadlock() {
+    let cell = ThreadCell::new_disowned(234);
+    cell.acquire();
+    let deadlock = cell.acquire_wait().unwrap_err();
+    assert_eq!(deadlock.thread_ids().count(), 1);
+}
+
+#[test]
+fn deadlock() {
+    let first = ThreadCell::new_disowned(1);
+    let second = ThreadCell::new_disowned(2);
+    let barrier = Barrier::new(2);
+
+    let run = |own: &ThreadCell<i32>, want: &ThreadCell<i32>| {
+        let owned = own.acquire_guard();
+        barrier.wait();
+        match want.acquire_guard_wait() {
+            Ok(wanted) => {
+                drop(wanted);
+                drop(owned);
+                None
+            }
+            Err(deadlock) => {
+                drop(owned);
+                Some(deadlock)
+            }
+        }
+    };
+
+    let (a, b) = std::thread::scope(|scope| {
+        let a = std::thread::Builder::new()
+            .name("a".into())
+            .spawn_scoped(scope, || run(&first, &second))
+            .unwrap();
+        let b = std::thread::Builder::new()
+            .name("b".into())
+            .spawn_scoped(scope, || run(&second, &first))
+            .unwrap();
+        (a.join().unwrap(), b.join().unwrap())
+    });
+
+    let deadlock = a.xor(b).expect("exactly one thread detects the deadlock");
+    assert_eq!(deadlock.thread_ids().count(), 2);
+    let message = deadlock.to_string();
+    assert!(
+        message == "Deadlock waiting for ThreadCell: `a` -> `b` -> `a`"
+            || message == "Deadlock waiting for ThreadCell: `b` -> `a` -> `b`",
+        "{message}"
+    );
+}
+
+#[test]
+fn frozen_while_waiting() {
+    static FROZEN: ThreadCell<i32> = ThreadCell::new_disowned(123);
+    static OTHER: ThreadCell<i32> = ThreadCell::new_disowned(234);
+    let barrier = Barrier::new(2);
+
+    FROZEN.acquire();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            barrier.wait();
+            let result = std::panic::catch_unwind(|| FROZEN.acquire_wait());
+            assert!(result.is_err());
+            let _guard = OTHER.acquire_guard();
+            barrier.wait();
+            barrier.wait();
+        });
+
+        barrier.wait();
+        std::thread::sleep(Duration::from_millis(10));
+        FROZEN.freeze();
+        barrier.wait();
+        // the waiter gave up, it does not wait for the current thread anymore
+        let result = OTHER.try_acquire_wait(Duration::from_millis(10));
+        barrier.wait();
+        assert_eq!(result, Ok(false));
+    });
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "std")]
+use std::time::Duration;
+use threadcell::channel::{self, RecvTimeoutError, TryRecvError};
+use threadcell::ThreadCell;
+
+#[test]
+fn pipeline() {
+    let (to_worker, from_main) = channel::channel::<Vec<u8>>();
+    let (to_main, from_worker) = channel::channel();
+
+    let worker = std::thread::spawn(move || {
+        while let Ok(mut cell) = from_main.recv() {
+            cell.get_mut().push(2);
+            to_main.send(cell).unwrap();
+        }
+    });
+
+    to_worker.send(ThreadCell::new_owned(vec![1])).unwrap();
+    let buffer = from_worker.recv().unwrap();
+    assert!(buffer.is_owned());
+    assert_eq!(*buffer.get(), [1, 2]);
+
+    drop(to_worker);
+    worker.join().unwrap();
+}
+
+#[test]
+fn disowned_in_channel() {
+    let (sender, receiver) = channel::channel();
+    sender.send(ThreadCell::new_owned(234)).unwrap();
+
+    // dropping queued cells on another thread is fine
+    std::thread::spawn(move || drop(receiver)).join().unwrap();
+}
+
+#[test]
+fn send_error_keeps_ownership() {
+    let (sender, receiver) = channel::channel();
+    drop(receiver);
+
+    let cell = sender.send(ThreadCell::new_owned(234)).unwrap_err().0;
+    assert!(cell.is_owned());
+}
+
+#[test]
+fn try_recv() {
+    let (sender, receiver) = channel::channel();
+    assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+    assert!(matches!(
+        receiver.recv_timeout(Duration::from_millis(1)),
+        Err(RecvTimeoutError::Timeout)
+    ));
+
+    std::thread::spawn(move || sender.send(ThreadCell::new_disowned(234)).unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(*receiver.try_recv().unwrap().get(), 234);
+}
+
+#[test]
+#[should_panic(expected = "Thread can not send ThreadCell, it is frozen")]
+fn send_frozen() {
+    let (sender, _receiver) = channel::channel();
+    let cell = ThreadCell::new_disowned(234);
+    cell.freeze();
+    let _ = sender.send(cell);
+}
+
+#[test]
+fn send_keeps_shared() {
+    let (sender, receiver) = channel::channel();
+    let cell = ThreadCell::new_owned(234);
+    assert_eq!(*cell.get_shared(), 234);
+    sender.send(cell).unwrap();
+
+    let cell = receiver.recv().unwrap();
+    assert!(cell.is_owned());
+    // the value was shared before, it stays so
+    let result = std::panic::catch_unwind(|| cell.replace(0));
+    assert!(result.is_err());
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "std")]
+use std::cell::Cell;
+use std::mem::size_of;
+use std::sync::Arc;
+use threadcell::SmallThreadCell;
+
+#[test]
+fn smaller_than_threadcell() {
+    assert!(size_of::<SmallThreadCell<u32>>() < size_of::<threadcell::ThreadCell<u32>>());
+}
+
+// debug builds add the rank
+#[test]
+#[cfg(not(debug_assertions))]
+fn no_overhead() {
+    assert_eq!(size_of::<threadcell::ThreadCell<u64>>(), 16);
+    assert_eq!(size_of::<SmallThreadCell<u32>>(), 8);
+}
+
+#[test]
+fn across_threads() {
+    static CELL: SmallThreadCell<i32> = SmallThreadCell::new_disowned(234);
+
+    let guard = CELL.acquire_guard();
+    std::thread::spawn(|| {
+        assert!(CELL.try_acquire_guard().is_none());
+        assert!(CELL.try_get().is_none());
+    })
+    .join()
+    .unwrap();
+    assert_eq!(*guard, 234);
+    drop(guard);
+
+    std::thread::spawn(|| {
+        assert_eq!(*CELL.acquire_guard(), 234);
+    })
+    .join()
+    .unwrap();
+    assert!(CELL.is_disowned());
+}
+
+#[test]
+fn recycled_ids_stay_small() {
+    let cell = Arc::new(SmallThreadCell::new_disowned(Cell::new(0)));
+
+    for _ in 0..1000 {
+        let cell = cell.clone();
+        std::thread::spawn(move || {
+            let counter = cell.acquire_guard();
+            counter.set(counter.get() + 1);
+        })
+        .join()
+        .unwrap();
+    }
+    assert_eq!(cell.acquire_guard().get(), 1000);
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "std")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use threadcell::{DeferredCell, ThreadCell};
+
+struct Counted(&'static AtomicUsize);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn dropped_by_owner() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let cell = DeferredCell::new_owned(Counted(&DROPS));
+    std::thread::spawn(move || drop(cell)).join().unwrap();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+    threadcell::run_deferred_drops();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn dropped_on_acquire() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    let other = ThreadCell::new_disowned(());
+
+    let cell = DeferredCell::new_owned(Counted(&DROPS));
+    let guard = other.acquire_guard();
+    std::thread::spawn(move || drop(cell)).join().unwrap();
+
+    // releasing never drops foreign values
+    drop(guard);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+    let guard = other.acquire_guard();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    drop(guard);
+}
+
+#[test]
+fn dropped_on_exit() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let (dropped, wait) = mpsc::channel();
+
+    let owner = std::thread::spawn(move || {
+        sender
+            .send(DeferredCell::new_owned(Counted(&DROPS)))
+            .unwrap();
+        wait.recv().unwrap();
+    });
+
+    drop(receiver.recv().unwrap());
+    dropped.send(()).unwrap();
+    owner.join().unwrap();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn panic_resumed_by_run_deferred_drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Panics;
+
+    impl Drop for Panics {
+        fn drop(&mut self) {
+            panic!("drop failed");
+        }
+    }
+
+    let panics = DeferredCell::new_owned(Panics);
+    let counted = DeferredCell::new_owned(Counted(&DROPS));
+    std::thread::spawn(move || drop((panics, counted)))
+        .join()
+        .unwrap();
+
+    let result = std::panic::catch_unwind(threadcell::run_deferred_drops);
+    assert_eq!(
+        result.unwrap_err().downcast_ref::<&str>(),
+        Some(&"drop failed")
+    );
+    // the remaining values are dropped before the panic is resumed
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    threadcell::run_deferred_drops();
+}
+
+#[test]
+fn dropped_while_unwinding() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    let cell = DeferredCell::new_owned(Counted(&DROPS));
+    let result = std::thread::spawn(move || {
+        let _cell = cell;
+        panic!("worker failed");
+    })
+    .join();
+    assert!(result.is_err());
+
+    threadcell::run_deferred_drops();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn not_dropped_by_other_thread() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    let owner = std::thread::spawn(move || {
+        let cell = DeferredCell::new_owned(Counted(&DROPS));
+        sender.send(cell).unwrap();
+    });
+    owner.join().unwrap();
+
+    // the owner is gone, its value is never dropped
+    drop(receiver.recv().unwrap());
+    threadcell::run_deferred_drops();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+}
@@ -0,0 +1,64 @@
+#![cfg(all(debug_assertions, feature = "diagnostics"))]
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use threadcell::ThreadCell;
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+#[test]
+fn names_conflicting_owner() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(0);
+    let (acquired_tx, acquired_rx) = std::sync::mpsc::channel();
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+
+    let worker = std::thread::Builder::new()
+        .name("worker-3".into())
+        .spawn(move || {
+            let _guard = CELL.acquire_guard();
+            acquired_tx.send(line!() - 1).unwrap();
+            done_rx.recv().unwrap();
+        })
+        .unwrap();
+
+    let line = acquired_rx.recv().unwrap();
+    let message = panic_message(catch_unwind(AssertUnwindSafe(|| CELL.acquire())).unwrap_err());
+    done_tx.send(()).unwrap();
+    worker.join().unwrap();
+
+    assert!(message.starts_with("Thread can not acquire ThreadCell"));
+    assert!(message.contains("thread `worker-3`"), "{message}");
+    assert!(
+        message.contains(&format!("tests/diagnostics.rs:{line}")),
+        "{message}"
+    );
+}
+
+#[test]
+fn names_current_thread() {
+    let cell = ThreadCell::new_disowned(0);
+    cell.acquire();
+    let message = panic_message(catch_unwind(AssertUnwindSafe(|| cell.acquire())).unwrap_err());
+    assert!(
+        message.contains("acquired at tests/diagnostics.rs"),
+        "{message}"
+    );
+}
+
+#[test]
+fn released_is_forgotten() {
+    let cell = ThreadCell::new_disowned(0);
+    drop(cell.acquire_guard());
+    let (_guard, line) = (cell.acquire_guard(), line!());
+    let message = panic_message(catch_unwind(AssertUnwindSafe(|| cell.acquire())).unwrap_err());
+    assert!(
+        message.contains(&format!("tests/diagnostics.rs:{line}")),
+        "{message}"
+    );
+}
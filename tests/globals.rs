@@ -1,3 +1,4 @@
+#![cfg(feature = "std")]
 use std::cell::RefCell;
 use threadcell::ThreadCell;
 static GLOBAL: ThreadCell<RefCell<u64>> = ThreadCell::new_disowned(RefCell::new(345));
@@ -13,6 +14,7 @@ fn access_global() {
 static mut MUT_GLOBAL: ThreadCell<u64> = ThreadCell::new_disowned(345);
 
 #[test]
+#[allow(static_mut_refs)]
 fn access_mut_global() {
     unsafe {
         MUT_GLOBAL.acquire();
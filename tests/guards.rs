@@ -1,3 +1,4 @@
+#![cfg(feature = "std")]
 use threadcell::ThreadCell;
 
 #[test]
@@ -45,21 +46,69 @@ fn guard_drop() {
 
 #[test]
 fn guard_mut() {
-    static mut DISOWNED: ThreadCell<i32> = ThreadCell::new_disowned(234);
+    static DISOWNED: ThreadCell<i32> = ThreadCell::new_disowned(234);
 
-    let mut guard = unsafe { DISOWNED.acquire_guard_mut() };
+    let mut guard = DISOWNED.acquire_guard_mut();
     assert_eq!(*guard, 234);
     *guard = 345;
     drop(guard);
 
     std::thread::spawn(|| {
-        let guard = unsafe { DISOWNED.acquire_guard() };
+        let guard = DISOWNED.acquire_guard();
         assert_eq!(*guard, 345);
     })
     .join()
     .unwrap();
 }
 
+#[test]
+fn with_mut_static() {
+    static DISOWNED: ThreadCell<Vec<i32>> = ThreadCell::new_disowned(Vec::new());
+
+    std::thread::spawn(|| DISOWNED.with_mut(|v| v.push(234)))
+        .join()
+        .unwrap();
+    DISOWNED.with_mut(|v| v.push(345));
+    assert_eq!(DISOWNED.with(Vec::clone), [234, 345]);
+}
+
+#[test]
+fn guard_mut_is_exclusive() {
+    let threadcell: ThreadCell<i32> = ThreadCell::new_disowned(0);
+
+    let guard = threadcell.acquire_guard_mut();
+    assert!(threadcell.is_exclusive());
+    assert!(threadcell.is_guarded());
+    assert!(!threadcell.is_owned());
+    assert!(threadcell.try_get().is_none());
+    assert!(threadcell.try_acquire_guard().is_none());
+    assert!(!threadcell.try_release());
+    drop(guard);
+    assert!(threadcell.is_disowned());
+}
+
+#[test]
+#[should_panic]
+fn get_while_guard_mut() {
+    let threadcell: ThreadCell<i32> = ThreadCell::new_disowned(0);
+
+    let _guard = threadcell.acquire_guard_mut();
+    threadcell.get();
+}
+
+#[test]
+#[should_panic(expected = "ThreadCell value is shared")]
+fn guard_mut_after_get() {
+    let threadcell: ThreadCell<i32> = ThreadCell::new_disowned(0);
+
+    let guard = threadcell.acquire_guard();
+    let value: &i32 = threadcell.get();
+    drop(guard);
+    // would alias `value`
+    let _guard = threadcell.acquire_guard_mut();
+    assert_eq!(*value, 0);
+}
+
 #[test]
 fn try_acquire_guard() {
     let threadcell: ThreadCell<i32> = ThreadCell::new_disowned(0);
@@ -70,7 +119,7 @@ fn try_acquire_guard() {
 
 #[test]
 fn try_acquire_guard_mut() {
-    let mut threadcell: ThreadCell<i32> = ThreadCell::new_disowned(0);
+    let threadcell: ThreadCell<i32> = ThreadCell::new_disowned(0);
 
     *threadcell.try_acquire_guard_mut().expect("Some(Guard)") = 234;
     assert_eq!(*threadcell.acquire_get(), 234);
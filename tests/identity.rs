@@ -0,0 +1,69 @@
+use std::cell::Cell;
+use std::num::NonZeroU64;
+use std::sync::atomic::AtomicU32;
+use threadcell::{OwnerCell, OwnerIdentity};
+
+thread_local!(static FAKE_ID: Cell<u64> = const { Cell::new(1) });
+
+/// A test controlled identity, every thread starts as owner 1.
+struct Fake;
+
+unsafe impl OwnerIdentity for Fake {
+    type Word = AtomicU32;
+
+    fn current() -> NonZeroU64 {
+        NonZeroU64::new(FAKE_ID.with(Cell::get)).unwrap()
+    }
+}
+
+fn become_owner(id: u64) {
+    FAKE_ID.with(|fake| fake.set(id));
+}
+
+#[test]
+fn multiple_owners() {
+    become_owner(1);
+    let cell: OwnerCell<i32, Fake> = OwnerCell::new_disowned(234);
+    cell.acquire();
+
+    become_owner(2);
+    assert!(!cell.is_owned());
+    assert!(cell.try_get().is_none());
+    assert!(!cell.try_acquire());
+
+    become_owner(1);
+    assert_eq!(*cell.get(), 234);
+    unsafe { cell.release() };
+
+    become_owner(2);
+    assert_eq!(*cell.acquire_get(), 234);
+}
+
+#[test]
+fn guards() {
+    static CELL: OwnerCell<i32, Fake> = OwnerCell::new_disowned(234);
+
+    become_owner(3);
+    let guard = CELL.acquire_guard();
+
+    become_owner(4);
+    assert!(CELL.try_acquire_guard().is_none());
+
+    become_owner(3);
+    drop(guard);
+
+    become_owner(4);
+    assert_eq!(*CELL.acquire_guard(), 234);
+}
+
+#[test]
+fn foreign_access() {
+    become_owner(5);
+    let cell: OwnerCell<i32, Fake> = OwnerCell::new_owned(234);
+
+    become_owner(6);
+    assert!(std::panic::catch_unwind(|| *cell.get()).is_err());
+
+    become_owner(5);
+    assert_eq!(*cell.get(), 234);
+}
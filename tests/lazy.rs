@@ -0,0 +1,86 @@
+#![cfg(feature = "std")]
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use threadcell::{LazyThreadCell, ThreadOnceCell};
+
+#[test]
+fn lazy_static_init_once() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    static MAP: LazyThreadCell<HashMap<u32, &str>> = LazyThreadCell::new(|| {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        HashMap::from([(1, "one")])
+    });
+
+    assert!(MAP.try_get().is_none());
+    assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+
+    std::thread::spawn(|| {
+        assert_eq!(MAP.with(|map| map[&1]), "one");
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(MAP.with(|map| map.len()), 1);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn lazy_not_owned() {
+    let lazy: LazyThreadCell<Vec<i32>> = LazyThreadCell::new(|| Vec::with_capacity(100));
+    let guard = lazy.acquire_guard();
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            assert!(lazy.try_get().is_none());
+            assert!(lazy.try_with(|v| v.capacity()).is_none());
+        });
+    });
+
+    assert!(lazy.get().capacity() >= 100);
+    drop(guard);
+}
+
+#[test]
+fn lazy_get_mut() {
+    let mut lazy = LazyThreadCell::new(|| vec![1, 2, 3]);
+    lazy.acquire();
+    lazy.get_mut().push(4);
+    assert_eq!(lazy.get(), &[1, 2, 3, 4]);
+    unsafe { lazy.release() };
+}
+
+#[test]
+fn once_get_or_init() {
+    static ONCE: ThreadOnceCell<String> = ThreadOnceCell::new();
+
+    std::thread::spawn(|| {
+        let _guard = ONCE.acquire_guard();
+        assert_eq!(ONCE.get(), None);
+        assert_eq!(ONCE.get_or_init(|| String::from("first")), "first");
+    })
+    .join()
+    .unwrap();
+
+    assert!(ONCE.try_get_or_init(|| String::from("second")).is_none());
+
+    let _guard = ONCE.acquire_guard();
+    assert_eq!(ONCE.get_or_init(|| String::from("second")), "first");
+    assert_eq!(ONCE.set(String::from("third")), Err(String::from("third")));
+}
+
+#[test]
+#[should_panic]
+fn once_not_owned() {
+    let once = ThreadOnceCell::<i32>::default();
+    once.get_or_init(|| 1);
+}
+
+#[test]
+fn once_take() {
+    let mut once = ThreadOnceCell::new();
+    once.acquire();
+    assert_eq!(once.set(234), Ok(()));
+    assert_eq!(once.take(), Some(234));
+    assert_eq!(once.get(), None);
+    unsafe { once.release() };
+}
@@ -0,0 +1,122 @@
+//! Model checks the handoff protocols with loom. Run with:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+#![cfg(loom)]
+use loom::cell::UnsafeCell;
+use loom::sync::Arc;
+use loom::thread;
+use threadcell::ThreadCell;
+
+/// Counter whose accesses are tracked by loom, any unsynchronized access fails the model.
+struct Counter(UnsafeCell<u32>);
+
+impl Counter {
+    fn new() -> Self {
+        Counter(UnsafeCell::new(0))
+    }
+
+    fn increment(&self) {
+        self.0.with_mut(|value| unsafe { *value += 1 });
+    }
+
+    fn get(&self) -> u32 {
+        self.0.with(|value| unsafe { *value })
+    }
+}
+
+impl Drop for Counter {
+    fn drop(&mut self) {
+        self.increment();
+    }
+}
+
+#[test]
+fn acquire_release() {
+    loom::model(|| {
+        let cell = Arc::new(ThreadCell::new_disowned(Counter::new()));
+
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let cell = cell.clone();
+                thread::spawn(move || {
+                    if cell.try_acquire() {
+                        cell.get().increment();
+                        unsafe { cell.release() };
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert!(cell.acquire_get().get() >= 1);
+        unsafe { cell.release() };
+    });
+}
+
+#[test]
+fn guard() {
+    loom::model(|| {
+        let cell = Arc::new(ThreadCell::new_disowned(Counter::new()));
+
+        let other = {
+            let cell = cell.clone();
+            thread::spawn(move || {
+                if let Some(guard) = cell.try_acquire_guard() {
+                    guard.increment();
+                }
+            })
+        };
+
+        if let Some(guard) = cell.try_acquire_guard() {
+            guard.increment();
+        }
+
+        other.join().unwrap();
+        assert!(cell.acquire_guard().get() >= 1);
+    });
+}
+
+#[test]
+fn steal() {
+    loom::model(|| {
+        let cell = Arc::new(ThreadCell::new_disowned(Counter::new()));
+
+        let other = {
+            let cell = cell.clone();
+            thread::spawn(move || {
+                // leaves the cell acquired, as a panicking thread would
+                cell.acquire();
+                cell.get().increment();
+            })
+        };
+
+        other.join().unwrap();
+        let stolen = unsafe { cell.steal() };
+        assert_eq!(stolen.get().get(), 1);
+    });
+}
+
+#[test]
+fn drop_race() {
+    loom::model(|| {
+        let cell = Arc::new(ThreadCell::new_disowned(Counter::new()));
+
+        let other = {
+            let cell = cell.clone();
+            thread::spawn(move || {
+                if let Some(guard) = cell.try_acquire_guard() {
+                    guard.increment();
+                }
+                drop(cell);
+            })
+        };
+
+        // Whichever thread drops the last reference drops the counter, which accesses it.
+        drop(cell);
+        other.join().unwrap();
+    });
+}
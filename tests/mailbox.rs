@@ -0,0 +1,162 @@
+#![cfg(feature = "std")]
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use threadcell::{poll_mailbox, ThreadCell};
+
+#[test]
+fn post_to_owner() {
+    static CELL: ThreadCell<Cell<i32>> = ThreadCell::new_disowned(Cell::new(0));
+    let (acquired, wait) = mpsc::channel();
+    let (posted, resume) = mpsc::channel();
+
+    let owner = thread::spawn(move || {
+        CELL.acquire();
+        acquired.send(()).unwrap();
+        resume.recv().unwrap();
+        poll_mailbox();
+        let value = CELL.get().get();
+        unsafe { CELL.release() };
+        value
+    });
+
+    wait.recv().unwrap();
+    let (ran_on, ran) = mpsc::channel();
+    CELL.post(move |value| {
+        value.set(value.get() + 1);
+        ran_on.send(thread::current().id()).unwrap();
+    });
+    assert!(ran.try_recv().is_err());
+    posted.send(()).unwrap();
+
+    let owner_id = owner.thread().id();
+    assert_eq!(owner.join().unwrap(), 1);
+    assert_eq!(ran.recv().unwrap(), owner_id);
+}
+
+#[test]
+fn post_disowned_runs_now() {
+    static CELL: ThreadCell<Cell<i32>> = ThreadCell::new_disowned(Cell::new(123));
+
+    CELL.post(|value| value.set(234));
+
+    assert!(CELL.is_disowned());
+    assert_eq!(CELL.with(Cell::get), 234);
+}
+
+#[test]
+fn release_runs_posted() {
+    static CELL: ThreadCell<Cell<i32>> = ThreadCell::new_disowned(Cell::new(0));
+    static OTHER: ThreadCell<()> = ThreadCell::new_disowned(());
+    let barrier = Arc::new(Barrier::new(2));
+
+    let owner = thread::spawn({
+        let barrier = barrier.clone();
+        move || {
+            CELL.acquire();
+            barrier.wait();
+            barrier.wait();
+            // acquiring an unrelated cell does not run it
+            OTHER.acquire();
+            let before = CELL.get().get();
+            unsafe { OTHER.release() };
+            let after = CELL.get().get();
+            unsafe { CELL.release() };
+            (before, after)
+        }
+    });
+
+    barrier.wait();
+    CELL.post(|value| value.set(345));
+    barrier.wait();
+
+    assert_eq!(owner.join().unwrap(), (0, 345));
+}
+
+#[test]
+fn panic_resumed_by_poll_mailbox() {
+    static CELL: ThreadCell<Cell<i32>> = ThreadCell::new_disowned(Cell::new(0));
+    let (acquired, wait) = mpsc::channel();
+    let (posted, resume) = mpsc::channel();
+
+    let owner = thread::spawn(move || {
+        CELL.acquire();
+        acquired.send(()).unwrap();
+        resume.recv().unwrap();
+        let result = std::panic::catch_unwind(poll_mailbox);
+        let value = CELL.get().get();
+        unsafe { CELL.release() };
+        (result.is_err(), value)
+    });
+
+    wait.recv().unwrap();
+    CELL.post(|_| panic!("closure failed"));
+    CELL.post(|value| value.set(456));
+    posted.send(()).unwrap();
+
+    // the remaining closures run before the panic is resumed
+    assert_eq!(owner.join().unwrap(), (true, 456));
+}
+
+#[test]
+fn call_on_owner() {
+    let cell = Arc::new(ThreadCell::new_disowned(Cell::new(234)));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (acquired, wait) = mpsc::channel();
+
+    let owner = thread::spawn({
+        let cell = cell.clone();
+        let stop = stop.clone();
+        move || {
+            cell.acquire();
+            acquired.send(()).unwrap();
+            while !stop.load(Ordering::Relaxed) {
+                poll_mailbox();
+                thread::yield_now();
+            }
+            unsafe { cell.release() };
+        }
+    });
+
+    wait.recv().unwrap();
+    assert_eq!(cell.call_on_owner(|value| value.get() * 2), 468);
+    stop.store(true, Ordering::Relaxed);
+    owner.join().unwrap();
+}
+
+#[test]
+fn call_on_each_other() {
+    let first = Arc::new(ThreadCell::new_disowned(Cell::new(1)));
+    let second = Arc::new(ThreadCell::new_disowned(Cell::new(2)));
+    let barrier = Arc::new(Barrier::new(2));
+
+    let other = thread::spawn({
+        let (first, second, barrier) = (first.clone(), second.clone(), barrier.clone());
+        move || {
+            second.acquire();
+            barrier.wait();
+            let result = first.call_on_owner(Cell::get);
+            // runs the call of the main thread unless it ran already
+            unsafe { second.release() };
+            result
+        }
+    });
+
+    first.acquire();
+    barrier.wait();
+    let result = second.call_on_owner(Cell::get);
+    unsafe { first.release() };
+
+    assert_eq!(result, 2);
+    assert_eq!(other.join().unwrap(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Thread has no access to ThreadCell")]
+fn call_on_owner_guarded() {
+    let cell = ThreadCell::new_disowned(123);
+    let _guard = cell.acquire_guard_mut();
+    cell.call_on_owner(|value| *value);
+}
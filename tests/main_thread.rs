@@ -0,0 +1,43 @@
+#![cfg(all(feature = "std", target_has_atomic = "64"))]
+use std::cell::RefCell;
+use std::rc::Rc;
+use threadcell::{is_main_thread, mark_main_thread, MainThreadCell};
+
+static STATE: MainThreadCell<RefCell<Vec<Rc<i32>>>> = MainThreadCell::new(|| RefCell::new(vec![]));
+
+#[test]
+fn main_thread() {
+    // the test harness runs tests on their own threads
+    mark_main_thread();
+    mark_main_thread();
+    assert!(is_main_thread());
+
+    STATE.get().borrow_mut().push(Rc::new(234));
+    assert!(STATE.is_owned());
+
+    std::thread::spawn(|| {
+        assert!(!is_main_thread());
+        assert!(!STATE.is_owned());
+        assert!(STATE.try_get().is_none());
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(*STATE.get().borrow()[0], 234);
+
+    // a thread name proves nothing
+    std::thread::Builder::new()
+        .name(String::from("main"))
+        .spawn(|| {
+            assert!(!is_main_thread());
+            let result = std::panic::catch_unwind(|| STATE.get().borrow().len());
+            assert!(result.is_err());
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let mut local = MainThreadCell::new(|| 123);
+    *local.get_mut() += 111;
+    assert_eq!(*local.get(), 234);
+}
@@ -0,0 +1,22 @@
+#![cfg(all(feature = "std", target_has_atomic = "64"))]
+use threadcell::{is_main_thread, MainThreadCell};
+
+#[test]
+fn unmarked() {
+    assert!(!is_main_thread());
+}
+
+#[test]
+fn unmarked_try() {
+    let mut state = MainThreadCell::new(|| 234);
+    assert!(!state.is_owned());
+    assert!(state.try_get().is_none());
+    assert!(state.try_get_mut().is_none());
+}
+
+#[test]
+#[should_panic(expected = "mark_main_thread() was not called")]
+fn unmarked_get() {
+    static STATE: MainThreadCell<i32> = MainThreadCell::new(|| 234);
+    let _ = STATE.get();
+}
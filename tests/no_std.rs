@@ -0,0 +1,49 @@
+//! Exercises the crate built without `std` using a fake thread id source. Run with:
+//!
+//! ```text
+//! cargo test --no-default-features
+//! ```
+#![cfg(not(feature = "std"))]
+use std::num::NonZeroU64;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Once;
+use threadcell::ThreadCell;
+
+fn fake_thread_id() -> NonZeroU64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(1);
+    thread_local!(static ID: NonZeroU64 =
+        NonZeroU64::new(COUNTER.fetch_add(1, Ordering::Relaxed)).unwrap());
+    ID.with(|&id| id)
+}
+
+fn setup() {
+    static SETUP: Once = Once::new();
+    SETUP.call_once(|| unsafe { threadcell::set_thread_id_source(fake_thread_id) });
+}
+
+#[test]
+fn owned() {
+    setup();
+    let cell = ThreadCell::new_owned(234);
+    assert_eq!(*cell.get(), 234);
+}
+
+#[test]
+fn handoff() {
+    setup();
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(234);
+
+    let guard = CELL.acquire_guard();
+    std::thread::spawn(|| {
+        setup();
+        assert!(CELL.try_acquire_guard().is_none());
+    })
+    .join()
+    .unwrap();
+    drop(guard);
+
+    std::thread::spawn(|| *CELL.acquire_guard())
+        .join()
+        .map(|value| assert_eq!(value, 234))
+        .unwrap();
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "std")]
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use threadcell::ThreadCell;
+
+/// Returns `Pending` once, giving the test a chance to move the future to another thread.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+fn poll<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+#[test]
+fn poll_across_threads() {
+    // async blocks holding references across await points are `!Unpin`
+    let cell = Arc::pin(ThreadCell::new_disowned(async {
+        let value = 234;
+        let reference = &value;
+        YieldOnce(false).await;
+        *reference
+    }));
+
+    assert!(cell.as_ref().with_pinned(poll).is_pending());
+
+    let remote = cell.clone();
+    let result = std::thread::spawn(move || remote.as_ref().with_pinned(poll))
+        .join()
+        .unwrap();
+    assert_eq!(result, Poll::Ready(234));
+}
+
+#[test]
+fn guard_pinned() {
+    let cell = Box::pin(ThreadCell::new_disowned(String::from("pinned")));
+
+    let mut guard = cell.as_ref().acquire_guard_pinned();
+    guard.as_mut().get_mut().push_str(" value");
+    assert_eq!(*guard, "pinned value");
+    drop(guard);
+
+    assert!(cell.is_disowned());
+}
+
+#[test]
+fn try_guard_pinned_owned_elsewhere() {
+    static OWNED: ThreadCell<i32> = ThreadCell::new_disowned(123);
+    let cell = Pin::static_ref(&OWNED);
+
+    std::thread::spawn(|| OWNED.acquire()).join().unwrap();
+
+    assert!(cell.try_acquire_guard_pinned().is_none());
+    assert_eq!(cell.try_with_pinned(|value| *value), None);
+}
+
+#[test]
+#[should_panic(expected = "pinned")]
+fn with_mut_after_pinned() {
+    let cell = Box::pin(ThreadCell::new_disowned(123));
+    cell.as_ref().with_pinned(|_| {});
+    cell.with_mut(|value| *value = 234);
+}
+
+#[test]
+fn with_after_pinned() {
+    let cell = Box::pin(ThreadCell::new_disowned(123));
+    cell.as_ref().with_pinned(|_| {});
+    assert_eq!(cell.with(|value| *value), 123);
+    assert!(cell.is_disowned());
+}
@@ -0,0 +1,53 @@
+#![cfg(all(feature = "std", target_has_atomic = "64"))]
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use threadcell::PinnedCell;
+
+#[test]
+fn shared_rc() {
+    let rc = Rc::new(Cell::new(234));
+    let cell = Arc::new(PinnedCell::new(rc.clone()));
+
+    std::thread::spawn({
+        let cell = cell.clone();
+        move || {
+            assert!(!cell.is_owned());
+            assert!(cell.try_get().is_none());
+        }
+    })
+    .join()
+    .unwrap();
+
+    cell.get().set(345);
+    assert_eq!(rc.get(), 345);
+    assert_eq!(Rc::strong_count(&rc), 2);
+}
+
+#[test]
+fn get_other_thread() {
+    let cell = Arc::new(PinnedCell::new(Rc::new(234)));
+    let other = cell.clone();
+    let result = std::thread::spawn(move || **other.get()).join();
+    assert!(result.is_err());
+    assert_eq!(**cell.get(), 234);
+}
+
+#[test]
+fn dropped_by_other_thread() {
+    let rc = Rc::new(234);
+    let cell = PinnedCell::new(rc.clone());
+
+    std::thread::spawn(move || drop(cell)).join().unwrap();
+    assert_eq!(Rc::strong_count(&rc), 2);
+
+    threadcell::run_deferred_drops();
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn into_inner() {
+    let mut cell = PinnedCell::new(Rc::new(234));
+    *Rc::get_mut(cell.get_mut()).unwrap() = 345;
+    assert_eq!(*cell.into_inner(), 345);
+}
@@ -0,0 +1,87 @@
+#![cfg(all(debug_assertions, feature = "std"))]
+use threadcell::ThreadCell;
+
+#[test]
+fn in_order() {
+    let first = ThreadCell::new_disowned_ranked(1, 1);
+    let second = ThreadCell::new_disowned_ranked(2, 2);
+
+    let _first = first.acquire_guard();
+    let _second = second.acquire_guard();
+}
+
+#[test]
+fn reacquire_after_release() {
+    let first = ThreadCell::new_disowned_ranked(1, 1);
+    let second = ThreadCell::new_disowned_ranked(2, 2);
+
+    drop(second.acquire_guard());
+    let _first = first.acquire_guard();
+    let _second = second.acquire_guard();
+}
+
+#[test]
+fn unranked_are_ignored() {
+    let ranked = ThreadCell::new_disowned_ranked(1, 1);
+    let unranked = ThreadCell::new_disowned(2);
+
+    unranked.acquire();
+    let _ranked = ranked.acquire_guard();
+}
+
+#[test]
+#[should_panic(expected = "lock order violation")]
+fn out_of_order() {
+    let first = ThreadCell::new_disowned_ranked(1, 1);
+    let second = ThreadCell::new_disowned_ranked(2, 2);
+
+    second.acquire();
+    first.acquire();
+}
+
+#[test]
+#[should_panic(expected = "lock order violation")]
+fn same_rank() {
+    let first = ThreadCell::new_disowned_ranked(1, 1);
+    let second = ThreadCell::new_disowned_ranked(2, 1);
+
+    let _first = first.acquire_guard();
+    let _second = second.acquire_guard();
+}
+
+#[test]
+#[should_panic(expected = "lock order violation")]
+fn failed_release_keeps_rank() {
+    let first = ThreadCell::new_disowned_ranked(1, 1);
+    let second = ThreadCell::new_disowned_ranked(2, 2);
+
+    let _second = second.acquire_guard();
+    assert!(!second.try_release());
+    first.acquire();
+}
+
+#[test]
+fn try_out_of_order() {
+    let first = ThreadCell::new_disowned_ranked(1, 1);
+    let second = ThreadCell::new_disowned_ranked(2, 2);
+
+    let _second = second.acquire_guard();
+    assert!(first.try_with(|value| *value == 1).unwrap());
+    assert!(first.try_acquire());
+}
+
+#[test]
+fn violation_names_caller() {
+    let first = ThreadCell::new_disowned_ranked(1, 1);
+    let second = ThreadCell::new_disowned_ranked(2, 2);
+
+    let (_second, line) = (second.acquire_guard(), line!());
+    let message = *std::panic::catch_unwind(|| first.acquire())
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+    assert!(
+        message.contains(&format!("acquired at tests/ranks.rs:{line}")),
+        "{message}"
+    );
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "std")]
+use std::sync::Barrier;
+use threadcell::ThreadRefCell;
+
+#[test]
+fn borrow_static() {
+    static CELL: ThreadRefCell<Vec<i32>> = ThreadRefCell::new(Vec::new());
+
+    CELL.borrow_mut().push(234);
+    assert!(CELL.is_disowned());
+
+    std::thread::spawn(|| {
+        CELL.borrow_mut().push(345);
+        assert_eq!(*CELL.borrow(), [234, 345]);
+    })
+    .join()
+    .unwrap();
+
+    let first = CELL.borrow();
+    let second = CELL.borrow();
+    assert!(CELL.is_owned());
+    assert_eq!(first.len() + second.len(), 4);
+    drop(first);
+    assert!(CELL.is_owned());
+    drop(second);
+    assert!(CELL.is_disowned());
+}
+
+#[test]
+fn borrow_rules() {
+    let cell = ThreadRefCell::new(234);
+
+    let shared = cell.borrow();
+    assert!(cell.try_borrow_mut().is_none());
+    assert!(cell.try_borrow().is_some());
+    drop(shared);
+
+    let mut exclusive = cell.borrow_mut();
+    *exclusive = 345;
+    assert!(cell.try_borrow().is_none());
+    assert!(cell.try_borrow_mut().is_none());
+    drop(exclusive);
+
+    assert_eq!(*cell.borrow(), 345);
+}
+
+#[test]
+#[should_panic(expected = "already mutably borrowed")]
+fn borrow_while_borrowed_mut() {
+    let cell = ThreadRefCell::new(234);
+    let _exclusive = cell.borrow_mut();
+    let _shared = cell.borrow();
+}
+
+#[test]
+fn owned_by_other_thread() {
+    let cell = ThreadRefCell::new(234);
+    let barrier = Barrier::new(2);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let _shared = cell.borrow();
+            barrier.wait();
+            barrier.wait();
+        });
+
+        barrier.wait();
+        assert!(cell.try_borrow().is_none());
+        assert!(cell.try_borrow_mut().is_none());
+        barrier.wait();
+    });
+
+    assert_eq!(*cell.borrow(), 234);
+}
+
+#[test]
+fn get_mut_into_inner() {
+    let mut cell = ThreadRefCell::new(String::from("234"));
+    cell.get_mut().push('5');
+    assert_eq!(cell.into_inner(), "2345");
+}
@@ -0,0 +1,61 @@
+#![cfg(all(feature = "serde", feature = "std"))]
+use threadcell::serde::Disowned;
+use threadcell::ThreadCell;
+
+#[test]
+fn serialize_owned() {
+    let cell = ThreadCell::new_owned(vec![1, 2, 3]);
+    assert_eq!(serde_json::to_string(&cell).unwrap(), "[1,2,3]");
+}
+
+#[test]
+fn serialize_disowned() {
+    let cell = ThreadCell::new_disowned(234);
+    let error = serde_json::to_string(&cell).unwrap_err();
+    assert_eq!(error.to_string(), "Thread has no access to ThreadCell");
+}
+
+#[test]
+fn serialize_nested() {
+    static GLOBAL: ThreadCell<u32> = ThreadCell::new_disowned(234);
+    let _guard = GLOBAL.acquire_guard();
+    assert_eq!(serde_json::to_string(&[&GLOBAL]).unwrap(), "[234]");
+}
+
+#[test]
+fn serialize_guard() {
+    let cell = ThreadCell::new_disowned(234);
+    let guard = cell.acquire_guard();
+    assert_eq!(serde_json::to_string(&cell).unwrap(), "234");
+    assert_eq!(serde_json::to_string(&guard).unwrap(), "234");
+    drop(guard);
+
+    let mut guard = cell.acquire_guard_mut();
+    *guard += 1;
+    assert_eq!(serde_json::to_string(&guard).unwrap(), "235");
+    // `&cell` would alias the guards `&mut`
+    assert!(serde_json::to_string(&cell).is_err());
+}
+
+#[test]
+fn serialize_keeps_unshared() {
+    let cell = ThreadCell::new_disowned(234);
+    cell.acquire();
+    assert_eq!(serde_json::to_string(&cell).unwrap(), "234");
+    unsafe { cell.release() };
+    cell.with_mut(|value| *value = 345);
+    assert_eq!(cell.with(|value| *value), 345);
+}
+
+#[test]
+fn deserialize_owned() {
+    let cell: ThreadCell<Vec<u32>> = serde_json::from_str("[1,2,3]").unwrap();
+    assert_eq!(*cell.get(), [1, 2, 3]);
+}
+
+#[test]
+fn deserialize_disowned() {
+    let Disowned(cell): Disowned<Vec<u32>> = serde_json::from_str("[1,2,3]").unwrap();
+    assert!(cell.is_disowned());
+    assert_eq!(*cell.acquire_get(), [1, 2, 3]);
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "std")]
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use threadcell::ThreadCell;
+
+#[test]
+fn get_shared_non_owner() {
+    let cell = Arc::new(ThreadCell::new_owned(vec![1, 2, 3]));
+
+    let reader = thread::spawn({
+        let cell = cell.clone();
+        move || {
+            assert!(!cell.is_owned());
+            cell.get_shared().iter().sum::<i32>()
+        }
+    });
+
+    assert_eq!(reader.join().unwrap(), 6);
+    assert_eq!(cell.get().len(), 3);
+}
+
+#[test]
+#[should_panic(expected = "ThreadCell value is shared")]
+fn get_shared_refuses_guard_mut() {
+    let cell = ThreadCell::new_disowned(123);
+    assert_eq!(*cell.get_shared(), 123);
+    let _guard = cell.acquire_guard_mut();
+}
+
+#[test]
+fn get_shared_exclusive() {
+    let mut cell = ThreadCell::new_disowned(123);
+    {
+        let mut guard = cell.acquire_guard_mut();
+        *guard += 111;
+        assert!(cell.try_get_shared().is_none());
+    }
+    assert_eq!(*cell.get_shared(), 234);
+
+    // the owner still mutates through `&mut self`
+    cell.acquire();
+    *cell.get_mut() = 345;
+    assert_eq!(*cell.get_shared(), 345);
+}
+
+#[test]
+fn shared_guards() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(234);
+    let barrier = Arc::new(Barrier::new(3));
+
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let guard = CELL.acquire_shared_guard();
+                barrier.wait();
+                barrier.wait();
+                *guard
+            })
+        })
+        .collect();
+
+    barrier.wait();
+    assert!(CELL.is_shared());
+    assert!(!CELL.try_acquire());
+    assert!(CELL.try_acquire_guard_mut().is_none());
+    assert_eq!(CELL.with_shared(|value| *value), 234);
+    barrier.wait();
+
+    for reader in readers {
+        assert_eq!(reader.join().unwrap(), 234);
+    }
+    assert!(CELL.is_disowned());
+    assert_eq!(CELL.with_mut(|value| std::mem::replace(value, 345)), 234);
+}
+
+#[test]
+fn shared_guard_owned() {
+    let cell = ThreadCell::new_owned(123);
+    assert!(cell.try_acquire_shared_guard().is_none());
+    assert!(cell.try_release());
+    let guard = cell.try_acquire_shared_guard().unwrap();
+    assert_eq!(*guard, 123);
+    drop(guard);
+    assert!(cell.is_disowned());
+}
+
+#[test]
+fn post_joins_readers() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(123);
+    static RAN: AtomicI32 = AtomicI32::new(0);
+
+    let guard = CELL.acquire_shared_guard();
+    CELL.post(|value| RAN.store(*value, Ordering::Relaxed));
+    assert_eq!(RAN.load(Ordering::Relaxed), 123);
+    drop(guard);
+    assert!(CELL.is_disowned());
+}
+
+#[test]
+fn call_on_owner_while_reading() {
+    let cell = ThreadCell::new_disowned(123);
+    let guard = cell.acquire_shared_guard();
+    assert_eq!(cell.call_on_owner(|value| *value + 111), 234);
+    assert_eq!(*guard, 123);
+}
+
+#[test]
+#[should_panic(expected = "it is read by 1 shared guards")]
+fn acquire_shared() {
+    let cell = ThreadCell::new_disowned(123);
+    let _guard = cell.acquire_shared_guard();
+    cell.acquire();
+}
+
+#[test]
+fn freeze() {
+    static CONFIG: ThreadCell<Vec<&str>> = ThreadCell::new_disowned(Vec::new());
+
+    CONFIG.with_mut(|config| config.push("frozen"));
+    CONFIG.acquire();
+    CONFIG.freeze();
+    CONFIG.freeze();
+    assert!(CONFIG.is_frozen());
+    assert!(!CONFIG.is_owned());
+    assert!(!CONFIG.try_acquire());
+    assert!(CONFIG.try_acquire_guard().is_none());
+    assert!(CONFIG.try_acquire_shared_guard().is_none());
+
+    let readers: Vec<_> = (0..2)
+        .map(|_| thread::spawn(|| CONFIG.get()[0]))
+        .collect();
+    for reader in readers {
+        assert_eq!(reader.join().unwrap(), "frozen");
+    }
+    assert_eq!(CONFIG.try_get(), Some(&vec!["frozen"]));
+}
+
+#[test]
+#[should_panic(expected = "Thread has no access to ThreadCell")]
+fn freeze_refuses_mut() {
+    let mut cell = ThreadCell::new_owned(123);
+    cell.freeze();
+    *cell.get_mut() = 234;
+}
+
+#[test]
+#[should_panic(expected = "Thread can not freeze guarded ThreadCell")]
+fn freeze_guarded() {
+    let cell = ThreadCell::new_disowned(123);
+    let _guard = cell.acquire_guard();
+    cell.freeze();
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "std")]
 use threadcell::ThreadCell;
 
 #[test]
@@ -12,6 +13,12 @@ fn access_owned() {
     assert_eq!(*owned.get(), 123);
 }
 
+#[test]
+fn into_inner() {
+    let owned = ThreadCell::new_owned(String::from("123"));
+    assert_eq!(owned.into_inner(), "123");
+}
+
 #[test]
 fn is_acquired() {
     let owned = ThreadCell::new_disowned(123);
@@ -59,7 +66,7 @@ fn try_with() {
 
 #[test]
 fn try_with_mut() {
-    let mut threadcell = ThreadCell::new_disowned(234);
+    let threadcell = ThreadCell::new_disowned(234);
     threadcell.try_with_mut(|v| *v = 345).expect("Acquired");
     threadcell
         .try_with(|v| assert_eq!(*v, 345))
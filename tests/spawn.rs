@@ -0,0 +1,89 @@
+#![cfg(feature = "std")]
+use std::sync::mpsc;
+use threadcell::{spawn_scoped_with, spawn_with, ThreadCell};
+
+#[test]
+fn static_cell() {
+    static CELL: ThreadCell<Vec<i32>> = ThreadCell::new_disowned(Vec::new());
+
+    let handle = spawn_with(CELL.acquire_guard_mut(), |value| {
+        value.push(234);
+        value.len()
+    });
+    let (guard, result) = handle.join();
+    assert_eq!(result.unwrap(), 1);
+
+    assert_eq!(*guard, [234]);
+    drop(guard);
+    assert!(CELL.is_disowned());
+}
+
+#[test]
+fn in_transit() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(123);
+    let (running, wait) = mpsc::channel();
+    let (proceed, resume) = mpsc::channel();
+
+    let handle = spawn_with(CELL.acquire_guard_mut(), move |value| {
+        running.send(()).unwrap();
+        resume.recv().unwrap();
+        *value += 111;
+    });
+
+    wait.recv().unwrap();
+    assert!(CELL.try_get().is_none());
+    assert!(!CELL.try_acquire());
+    proceed.send(()).unwrap();
+
+    while !handle.is_finished() {
+        std::thread::yield_now();
+    }
+    // still in transit to the joining thread
+    assert!(!CELL.try_acquire());
+
+    let (guard, result) = handle.join();
+    result.unwrap();
+    assert_eq!(*guard, 234);
+}
+
+#[test]
+fn join_after_panic() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(123);
+
+    let handle = spawn_with(CELL.acquire_guard_mut(), |value| {
+        *value = 234;
+        panic!("expected panic");
+    });
+    let (guard, result) = handle.join();
+    assert!(result.is_err());
+
+    assert!(CELL.is_guarded());
+    assert_eq!(*guard, 234);
+}
+
+#[test]
+fn drop_handle() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(123);
+
+    drop(spawn_with(CELL.acquire_guard_mut(), |value| *value = 234));
+
+    assert!(CELL.is_disowned());
+    assert_eq!(CELL.with(|value| *value), 234);
+}
+
+#[test]
+fn scoped() {
+    let cell = ThreadCell::new_disowned(String::from("scoped"));
+
+    std::thread::scope(|scope| {
+        let handle = spawn_scoped_with(scope, cell.acquire_guard_mut(), |value| {
+            value.push_str(" thread")
+        });
+        let (guard, result) = handle.join();
+        result.unwrap();
+        assert_eq!(*guard, "scoped thread");
+    });
+
+    assert!(cell.is_disowned());
+    assert_eq!(cell.with(String::clone), "scoped thread");
+}
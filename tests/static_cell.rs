@@ -0,0 +1,44 @@
+#![cfg(feature = "std")]
+
+mod config {
+    threadcell::static_cell! {
+        /// Documented static.
+        pub static CONFIG: Vec<String> = Vec::new();
+        static PRIVATE: i32 = 234;
+    }
+
+    pub fn private() -> i32 {
+        PRIVATE::with(|v| *v)
+    }
+}
+
+use config::CONFIG;
+
+#[test]
+fn with_mut() {
+    std::thread::spawn(|| CONFIG::with_mut(|config| config.push(String::from("first"))))
+        .join()
+        .unwrap();
+    CONFIG::with_mut(|config| config.push(String::from("second")));
+    assert_eq!(CONFIG::with(Vec::len), 2);
+    assert_eq!(config::private(), 234);
+}
+
+threadcell::static_cell! {
+    static COUNTER: u32 = 0;
+}
+
+#[test]
+fn try_with() {
+    let guard = COUNTER::cell().acquire_guard();
+    std::thread::spawn(|| {
+        assert_eq!(COUNTER::try_with(|v| *v), None);
+        assert_eq!(COUNTER::try_with_mut(|v| *v += 1), None);
+    })
+    .join()
+    .unwrap();
+    drop(guard);
+
+    assert_eq!(COUNTER::try_with_mut(|v| *v += 1), Some(()));
+    assert_eq!(COUNTER::try_with(|v| *v), Some(1));
+}
@@ -0,0 +1,84 @@
+#![cfg(all(feature = "std", target_has_atomic = "64"))]
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use threadcell::task::{self, TaskCell};
+
+/// Returns `Pending` once, giving the test a chance to move the task to another thread.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+fn poll<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+    Pin::new(future).poll(&mut Context::from_waker(Waker::noop()))
+}
+
+#[test]
+fn guard_across_threads() {
+    let cell = Arc::new(TaskCell::new_disowned(234));
+
+    let mut future = Box::pin(task::scope({
+        let cell = cell.clone();
+        async move {
+            let guard = cell.acquire_guard();
+            YieldOnce(false).await;
+            *guard
+        }
+    }));
+
+    assert!(poll(&mut future).is_pending());
+    assert!(!cell.is_disowned());
+
+    let result = std::thread::spawn(move || poll(&mut future))
+        .join()
+        .unwrap();
+    assert_eq!(result, Poll::Ready(234));
+    assert!(cell.is_disowned());
+}
+
+#[test]
+fn tasks_are_distinct_owners() {
+    let cell = Arc::new(TaskCell::new_disowned(234));
+
+    let mut owner = Box::pin(task::scope({
+        let cell = cell.clone();
+        async move {
+            cell.acquire();
+            YieldOnce(false).await;
+            let value = *cell.get();
+            unsafe { cell.release() };
+            value
+        }
+    }));
+    assert!(poll(&mut owner).is_pending());
+
+    let mut other = Box::pin(task::scope({
+        let cell = cell.clone();
+        async move { cell.try_get().is_some() }
+    }));
+    assert_eq!(poll(&mut other), Poll::Ready(false));
+    assert!(cell.try_get().is_none());
+
+    assert_eq!(poll(&mut owner), Poll::Ready(234));
+}
+
+#[test]
+fn outside_task() {
+    let cell = TaskCell::new_owned(234);
+    assert_eq!(*cell.get(), 234);
+
+    let mut future = Box::pin(task::scope(async { cell.try_get().is_some() }));
+    assert_eq!(poll(&mut future), Poll::Ready(false));
+}
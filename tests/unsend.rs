@@ -0,0 +1,67 @@
+#![cfg(feature = "std")]
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use threadcell::UnsendCell;
+
+struct Node {
+    value: i32,
+    children: Vec<Rc<RefCell<Node>>>,
+}
+
+fn tree() -> Rc<RefCell<Node>> {
+    let leaf = Rc::new(RefCell::new(Node {
+        value: 2,
+        children: Vec::new(),
+    }));
+    Rc::new(RefCell::new(Node {
+        value: 1,
+        children: vec![leaf.clone(), leaf],
+    }))
+}
+
+fn sum(node: &Rc<RefCell<Node>>) -> i32 {
+    let node = node.borrow();
+    node.value + node.children.iter().map(sum).sum::<i32>()
+}
+
+#[test]
+fn hand_over_rc_graph() {
+    let cell = Arc::new(unsafe { UnsendCell::new_owned(tree()) });
+    assert_eq!(sum(unsafe { cell.get() }), 5);
+    unsafe { cell.release() };
+
+    std::thread::spawn({
+        let cell = cell.clone();
+        move || unsafe {
+            cell.with_mut(|tree| tree.borrow().children[0].borrow_mut().value = 3);
+        }
+    })
+    .join()
+    .unwrap();
+
+    cell.acquire();
+    assert_eq!(sum(unsafe { cell.get() }), 7);
+    unsafe { cell.release() };
+}
+
+#[test]
+fn into_inner() {
+    static CELL: UnsendCell<Option<Rc<i32>>> = unsafe { UnsendCell::new_disowned(None) };
+    assert!(CELL.try_acquire());
+    assert!(unsafe { CELL.get() }.is_none());
+    assert!(CELL.try_release());
+
+    let cell = std::thread::spawn(|| {
+        let cell = unsafe { UnsendCell::new_owned(Rc::new(234)) };
+        unsafe { cell.release() };
+        cell
+    })
+    .join()
+    .unwrap();
+    assert!(cell.is_disowned());
+    assert_eq!(unsafe { cell.with(|value| **value) }, 234);
+    cell.acquire();
+    assert!(cell.is_owned());
+    assert_eq!(*cell.into_inner(), 234);
+}
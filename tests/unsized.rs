@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use threadcell::{DeferredCell, ThreadCell};
+
+trait Plugin {
+    fn name(&self) -> &str;
+    fn run(&mut self) -> u32;
+}
+
+struct Counter(u32);
+
+impl Plugin for Counter {
+    fn name(&self) -> &str {
+        "counter"
+    }
+
+    fn run(&mut self) -> u32 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+#[test]
+fn boxed_trait_object() {
+    let plugins: Vec<Box<ThreadCell<dyn Plugin + Send>>> = vec![
+        Box::new(ThreadCell::new_disowned(Counter(0))),
+        Box::new(ThreadCell::new_disowned(Counter(10))),
+    ];
+
+    let results: Vec<u32> = plugins
+        .iter()
+        .map(|plugin| plugin.acquire_guard_mut().run())
+        .collect();
+    assert_eq!(results, [1, 11]);
+    assert_eq!(
+        plugins[0].with(|plugin| plugin.name().to_string()),
+        "counter"
+    );
+}
+
+#[test]
+fn arc_slice() {
+    let cell: Arc<ThreadCell<[u8]>> = Arc::new(ThreadCell::new_disowned([1, 2, 3]));
+
+    std::thread::spawn({
+        let cell = cell.clone();
+        move || cell.with_mut(|bytes| bytes[0] = 4)
+    })
+    .join()
+    .unwrap();
+
+    let guard = cell.acquire_guard();
+    assert_eq!(&*guard, [4, 2, 3]);
+}
+
+#[test]
+fn deferred_unsized() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let cell: Box<DeferredCell<dyn Any + Send>> = Box::new(DeferredCell::new_owned(Counted));
+    std::thread::spawn(move || drop(cell)).join().unwrap();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+    threadcell::run_deferred_drops();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+}
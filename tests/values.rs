@@ -0,0 +1,86 @@
+#![cfg(feature = "std")]
+use threadcell::ThreadCell;
+
+#[test]
+fn replace_take_set() {
+    let cell = ThreadCell::new_disowned(String::from("first"));
+    assert_eq!(cell.replace(String::from("second")), "first");
+    assert_eq!(cell.take(), "second");
+    assert_eq!(cell.with(String::clone), "");
+    cell.set(String::from("third"));
+    assert_eq!(cell.with(String::clone), "third");
+    assert!(cell.is_disowned());
+}
+
+#[test]
+fn update() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(233);
+    CELL.update(|v| v + 1);
+    assert_eq!(CELL.with(|v| *v), 234);
+}
+
+#[test]
+fn replace_owned() {
+    static CELL: ThreadCell<i32> = ThreadCell::new_disowned(233);
+    CELL.acquire();
+    assert_eq!(CELL.replace(234), 233);
+    CELL.update(|v| v + 1);
+    assert_eq!(*CELL.get(), 235);
+    unsafe { CELL.release() };
+}
+
+#[test]
+#[should_panic(expected = "ThreadCell value is shared")]
+fn replace_owned_shared() {
+    let cell = ThreadCell::new_owned(234);
+    let _value = cell.get();
+    cell.replace(0);
+}
+
+#[test]
+#[should_panic]
+fn replace_guarded() {
+    let cell = ThreadCell::new_disowned(234);
+    let _guard = cell.acquire_guard();
+    cell.replace(0);
+}
+
+#[test]
+fn replace_restores_on_panic() {
+    let cell = ThreadCell::new_owned(234);
+    let result = std::panic::catch_unwind(|| cell.update(|_| panic!("update failed")));
+    assert!(result.is_err());
+    assert!(cell.is_acquired());
+    assert_eq!(cell.replace(0), 234);
+}
+
+#[test]
+fn swap() {
+    static A: ThreadCell<i32> = ThreadCell::new_disowned(1);
+    static B: ThreadCell<i32> = ThreadCell::new_disowned(2);
+    A.swap(&B);
+    A.swap(&A);
+    assert_eq!((A.with(|v| *v), B.with(|v| *v)), (2, 1));
+}
+
+#[test]
+fn swap_owned() {
+    let a = ThreadCell::new_disowned(1);
+    let b = ThreadCell::new_owned(2);
+    a.swap(&b);
+    assert!(a.is_disowned());
+    assert_eq!(a.with(|v| *v), 2);
+    assert_eq!(*b.get(), 1);
+}
+
+#[test]
+fn map_into_keeps_ownership() {
+    let owned = ThreadCell::new_owned(234).map_into(|v| v.to_string());
+    assert!(owned.is_owned());
+    assert_eq!(*owned.get(), "234");
+
+    let disowned = ThreadCell::new_disowned(234).map_into(|v| v.to_string());
+    assert!(disowned.is_disowned());
+    assert_eq!(*disowned.acquire_get(), "234");
+    unsafe { disowned.release() };
+}